@@ -0,0 +1,111 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+
+// Module summary: an audit of the spam labels. It finds annotations that disagree with each other: authors with
+// both spam and ham comments, the same text labeled both ways, and near-duplicate clusters with both labels. The
+// result can be written to a CSV for the annotators to re-check.
+
+
+// what kind of disagreement a group of rows shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IssueKind {
+    MixedAuthor, // one author has spam and ham comments (often fine, but worth a look)
+    IdenticalText, // the same text (ignoring case and punctuation) is labeled spam and ham
+    NearDuplicate, // a near-duplicate cluster from find_near_duplicates() has both labels
+}
+
+
+// It is one group of rows whose labels disagree
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelIssue {
+    pub kind: IssueKind,
+    pub key: String, // the author, the text, or "cluster <n>"
+    pub spam_rows: Vec<usize>, // ascending
+    pub ham_rows: Vec<usize>, // ascending
+}
+
+
+// It holds every issue found, grouped by kind
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelAudit {
+    pub mixed_authors: Vec<LabelIssue>,
+    pub identical_texts: Vec<LabelIssue>,
+    pub near_duplicates: Vec<LabelIssue>,
+}
+
+
+// purpose: turn groups of rows into issues, keeping only the groups with both labels
+// input: the array of data (col 3 holds the label), the kind of issue, and (key, rows) groups
+// output: a vector of LabelIssues, the most rows first (ties by key)
+fn conflicting_groups(arr_data: &Array2<crate::ColumnVal>, kind: IssueKind, groups: Vec<(String, Vec<usize>)>) -> Vec<LabelIssue> {
+    let mut issues: Vec<LabelIssue> = groups.into_iter().filter_map(|(key, rows)| {
+        let (spam_rows, ham_rows): (Vec<usize>, Vec<usize>) = rows.into_iter().partition(|i| arr_data[[*i, 3]].as_bool() == Some(true));
+        if spam_rows.is_empty() || ham_rows.is_empty() { None } else { Some(LabelIssue { kind, key, spam_rows, ham_rows }) }
+    }).collect();
+    issues.sort_by(|a, b| (b.spam_rows.len() + b.ham_rows.len()).cmp(&(a.spam_rows.len() + a.ham_rows.len())).then_with(|| a.key.cmp(&b.key)));
+    issues
+}
+
+
+// purpose: audit the labels of the data
+// input: the array of data (cols 0-user, 1-content, 3-class) and the min similarity for near-duplicates
+// output: a LabelAudit
+// identical texts are compared by their tokens (tokenize() joined by spaces), so "Nice song!" and "nice song" match;
+// comments with no tokens are skipped. Near-duplicates use 5-character shingles and 20 bands of 4 MinHashes. A cluster
+// whose spam and ham rows all have one same text is left out, since that conflict is already an identical-text issue
+pub fn audit_labels(arr_data: &Array2<crate::ColumnVal>, min_similarity: f64) -> LabelAudit {
+    let mut by_author: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut by_text: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut texts: Vec<String> = Vec::new(); // the text key of every row
+    for i in 0..arr_data.nrows() {
+        by_author.entry(arr_data[[i, 0]].as_text().unwrap_or("").to_string()).or_default().push(i);
+        let text = crate::text_functions::tokenize(arr_data[[i, 1]].as_text().unwrap_or("")).join(" ");
+        if !text.is_empty() {
+            by_text.entry(text.clone()).or_default().push(i);
+        }
+        texts.push(text);
+    }
+
+    let clusters: Vec<(String, Vec<usize>)> = crate::duplicate_functions::find_near_duplicates(arr_data, 5, 20, 4, min_similarity).into_iter()
+        .enumerate()
+        .map(|(num, cluster)| (format!("cluster {}", num), cluster.rows))
+        .filter(|(_, rows)| {
+            let first = &texts[rows[0]];
+            first.is_empty() || rows.iter().any(|i| texts[*i] != *first)
+        })
+        .collect();
+
+    LabelAudit {
+        mixed_authors: conflicting_groups(arr_data, IssueKind::MixedAuthor, by_author.into_iter().collect()),
+        identical_texts: conflicting_groups(arr_data, IssueKind::IdenticalText, by_text.into_iter().collect()),
+        near_duplicates: conflicting_groups(arr_data, IssueKind::NearDuplicate, clusters),
+    }
+}
+
+
+impl LabelAudit {
+
+    // purpose: write the audit to a CSV file for the annotators
+    // input: the path to write and the array of data the audit came from
+    // output: an io Result. The file has a header row and one row per comment of every issue: issue kind, key, comment
+    // id, author, video, current label (spam/ham), content. Identical texts and near-duplicates come first, since those
+    // are the likeliest mistakes
+    pub fn write_report(&self, path: &str, arr_data: &Array2<crate::ColumnVal>) -> std::io::Result<()> {
+        let mut wtr = csv::Writer::from_path(path)?;
+        wtr.write_record(["issue", "key", "comment_id", "author", "video", "label", "content"])?;
+        for issue in self.identical_texts.iter().chain(self.near_duplicates.iter()).chain(self.mixed_authors.iter()) {
+            for (row, label) in issue.spam_rows.iter().map(|r| (r, "spam")).chain(issue.ham_rows.iter().map(|r| (r, "ham"))) {
+                wtr.write_record([
+                    format!("{:?}", issue.kind).as_str(),
+                    &issue.key,
+                    &crate::duplicate_functions::row_comment_id(arr_data, *row),
+                    arr_data[[*row, 0]].as_text().unwrap_or(""),
+                    arr_data[[*row, 2]].as_text().unwrap_or(""),
+                    label,
+                    arr_data[[*row, 1]].as_text().unwrap_or(""),
+                ])?;
+            }
+        }
+        wtr.flush()
+    }
+}
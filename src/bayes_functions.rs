@@ -0,0 +1,196 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+
+// Module summary: a multinomial Naive Bayes spam classifier. It is trained on the CONTENT (col 1) and CLASS (col 3)
+// columns of the array returned by read_CSV_using_reader(), and can be saved to / loaded from a plain text model file.
+// The file records a hash of the training data, so a model trained on other (e.g. not yet deduplicated) rows is retrained.
+
+// first line of every model file, so that we never load something that isn't a model
+const MODEL_HEADER: &str = "naive_bayes_v2";
+
+
+// It keeps the per-class token counts needed to score new comments. alpha is the Laplace smoothing constant
+#[derive(Debug, Clone, PartialEq)]
+pub struct NaiveBayesModel {
+    pub alpha: f64,
+    pub spam_docs: u32,
+    pub ham_docs: u32,
+    pub data_hash: u64, // csv_functions::data_hash() of the training data
+    pub spam_counts: HashMap<String, u32>,
+    pub ham_counts: HashMap<String, u32>,
+}
+
+impl NaiveBayesModel {
+
+    // purpose: train the model on labeled comments
+    // input: an array with cols 0-user, 1-content, 2-video name, 3-class, and the Laplace smoothing constant
+    // output: a trained NaiveBayesModel
+    // iterates over each row, tokenizes the content and adds every token occurrence to the count table of the row's class
+    pub fn train(arr_data: &Array2<crate::ColumnVal>, alpha: f64) -> NaiveBayesModel {
+        let mut model = NaiveBayesModel {
+            alpha,
+            spam_docs: 0,
+            ham_docs: 0,
+            data_hash: crate::csv_functions::data_hash(arr_data),
+            spam_counts: HashMap::new(),
+            ham_counts: HashMap::new(),
+        };
+
+        for row in arr_data.rows() {
+            let is_spam = row[3].as_bool().unwrap_or(false);
+            let content = row[1].as_text().unwrap_or("");
+            let counts = if is_spam {
+                model.spam_docs += 1;
+                &mut model.spam_counts
+            } else {
+                model.ham_docs += 1;
+                &mut model.ham_counts
+            };
+            for token in crate::text_functions::tokenize(content) {
+                *counts.entry(token).or_insert(0) += 1;
+            }
+        }
+        model
+    }
+
+    // purpose: find the number of distinct tokens seen during training (the |V| in Laplace smoothing)
+    // input: the model
+    // output: the vocabulary size
+    pub fn vocab_size(&self) -> usize {
+        let mut vocab: HashSet<&String> = self.spam_counts.keys().collect();
+        vocab.extend(self.ham_counts.keys());
+        vocab.len()
+    }
+
+    // purpose: find the probability that a comment is spam
+    // input: the comment content
+    // output: P(spam | content) between 0 and 1
+    // adds up the log prior and the smoothed log likelihood of each known token for both classes, then turns the
+    // difference of the two log scores back into a probability. Tokens never seen in training are ignored
+    pub fn spam_probability(&self, content: &str) -> f64 {
+        let total_docs = (self.spam_docs + self.ham_docs) as f64;
+        if total_docs == 0.0 {
+            return 0.5; // an untrained model has no opinion
+        }
+        let vocab_size = self.vocab_size() as f64;
+        let spam_total: f64 = self.spam_counts.values().map(|c| *c as f64).sum();
+        let ham_total: f64 = self.ham_counts.values().map(|c| *c as f64).sum();
+
+        // the prior is smoothed too, so a class with no documents doesn't produce ln(0)
+        let mut spam_score = ((self.spam_docs as f64 + self.alpha) / (total_docs + 2.0 * self.alpha)).ln();
+        let mut ham_score = ((self.ham_docs as f64 + self.alpha) / (total_docs + 2.0 * self.alpha)).ln();
+
+        for token in crate::text_functions::tokenize(content) {
+            let spam_count = self.spam_counts.get(&token).copied();
+            let ham_count = self.ham_counts.get(&token).copied();
+            if spam_count.is_none() && ham_count.is_none() {
+                continue;
+            }
+            let spam_count = spam_count.unwrap_or(0) as f64;
+            let ham_count = ham_count.unwrap_or(0) as f64;
+            spam_score += ((spam_count + self.alpha) / (spam_total + self.alpha * vocab_size)).ln();
+            ham_score += ((ham_count + self.alpha) / (ham_total + self.alpha * vocab_size)).ln();
+        }
+
+        // P(spam) = e^spam / (e^spam + e^ham) = 1 / (1 + e^(ham - spam))
+        1.0 / (1.0 + (ham_score - spam_score).exp())
+    }
+
+    // purpose: classify a comment
+    // input: the comment content
+    // output: true if the comment is more likely spam than not
+    pub fn predict(&self, content: &str) -> bool {
+        self.spam_probability(content) >= 0.5
+    }
+
+    // purpose: write the model to a text file so it can be reused without retraining
+    // input: the path of the file to write
+    // output: an io Result
+    // writes a header line, the smoothing constant, the document counts and the data hash, then one "token, spam count, ham count" line per
+    // token, tab separated. Tokens are alphanumeric so they never contain tabs or newlines. Lines are sorted so the
+    // same model always produces the same file
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let mut vocab: Vec<&String> = self.spam_counts.keys().chain(self.ham_counts.keys()).collect();
+        vocab.sort();
+        vocab.dedup();
+
+        let mut out = String::new();
+        out.push_str(&format!("{}\n", MODEL_HEADER));
+        out.push_str(&format!("alpha\t{}\n", self.alpha));
+        out.push_str(&format!("docs\t{}\t{}\n", self.spam_docs, self.ham_docs));
+        out.push_str(&format!("data\t{}\n", self.data_hash));
+        for token in vocab {
+            let spam_count = self.spam_counts.get(token).copied().unwrap_or(0);
+            let ham_count = self.ham_counts.get(token).copied().unwrap_or(0);
+            out.push_str(&format!("{}\t{}\t{}\n", token, spam_count, ham_count));
+        }
+        fs::write(path, out)
+    }
+
+    // purpose: tell whether the model was trained on this data with this smoothing
+    // input: the model, the array of data it would be used with and the alpha it should have been trained with
+    // output: true if the data has the same hash as the training data and the alpha is the same, false if the model
+    // should be retrained
+    pub fn trained_on(&self, arr_data: &Array2<crate::ColumnVal>, alpha: f64) -> bool {
+        self.alpha == alpha && self.data_hash == crate::csv_functions::data_hash(arr_data)
+    }
+
+    // purpose: read a model written by save()
+    // input: the path of the model file
+    // output: Ok(the model) or Err(a description of what was wrong with the file)
+    // checks the header line, then parses the alpha, docs and data lines followed by the token lines
+    pub fn load(path: &str) -> Result<NaiveBayesModel, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+        let mut lines = text.lines();
+
+        if lines.next() != Some(MODEL_HEADER) {
+            return Err(format!("{} is not a Naive Bayes model file", path));
+        }
+
+        let alpha_line: Vec<&str> = lines.next().unwrap_or("").split('\t').collect();
+        if alpha_line.len() != 2 || alpha_line[0] != "alpha" {
+            return Err(format!("missing alpha line in {}", path));
+        }
+        let alpha: f64 = alpha_line[1].parse().map_err(|_| format!("bad alpha in {}", path))?;
+
+        let docs_line: Vec<&str> = lines.next().unwrap_or("").split('\t').collect();
+        if docs_line.len() != 3 || docs_line[0] != "docs" {
+            return Err(format!("missing docs line in {}", path));
+        }
+        let spam_docs: u32 = docs_line[1].parse().map_err(|_| format!("bad spam doc count in {}", path))?;
+        let ham_docs: u32 = docs_line[2].parse().map_err(|_| format!("bad ham doc count in {}", path))?;
+
+        let data_line: Vec<&str> = lines.next().unwrap_or("").split('\t').collect();
+        if data_line.len() != 2 || data_line[0] != "data" {
+            return Err(format!("missing data line in {}", path));
+        }
+        let data_hash: u64 = data_line[1].parse().map_err(|_| format!("bad data hash in {}", path))?;
+
+        let mut model = NaiveBayesModel {
+            alpha,
+            spam_docs,
+            ham_docs,
+            data_hash,
+            spam_counts: HashMap::new(),
+            ham_counts: HashMap::new(),
+        };
+
+        for (num, line) in lines.enumerate() {
+            let parts: Vec<&str> = line.split('\t').collect();
+            if parts.len() != 3 {
+                return Err(format!("bad token line {} in {}", num + 5, path));
+            }
+            let spam_count: u32 = parts[1].parse().map_err(|_| format!("bad spam count on line {} in {}", num + 5, path))?;
+            let ham_count: u32 = parts[2].parse().map_err(|_| format!("bad ham count on line {} in {}", num + 5, path))?;
+            if spam_count > 0 {
+                model.spam_counts.insert(parts[0].to_string(), spam_count);
+            }
+            if ham_count > 0 {
+                model.ham_counts.insert(parts[0].to_string(), ham_count);
+            }
+        }
+        Ok(model)
+    }
+}
@@ -0,0 +1,137 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Module summary: the bipartite user <-> video graph built from the AUTHOR (col 0) and VIDEO_NAME (col 2) columns, and
+// its two projections. It is another lens next to the vocabulary-similarity graph: it shows which spammers hit the same
+// set of videos, and which videos share spammer populations.
+
+
+// It stores the bipartite graph from both sides: which videos each user commented on, and which users commented on each video
+#[derive(Debug, Clone, PartialEq)]
+pub struct BipartiteGraph {
+    pub user_videos: HashMap<String, HashSet<String>>,
+    pub video_users: HashMap<String, HashSet<String>>,
+}
+
+
+// purpose: turn a map of pair counts into a graph
+// input: every node, the # of shared neighbors of each pair (first < second), and the min # shared to keep an edge
+// output: a hashmap mapping each node to a sorted vector of its neighbors, the same shape create_graph() returns
+fn graph_from_pair_counts(nodes: Vec<&String>, pair_counts: &HashMap<(&String, &String), usize>, min_shared: usize) -> HashMap<String, Vec<String>> {
+    let mut graph: HashMap<String, Vec<String>> = nodes.into_iter().map(|n| (n.clone(), Vec::new())).collect();
+    for ((a, b), count) in pair_counts.iter() {
+        if *count >= min_shared {
+            graph.get_mut(*a).unwrap().push(b.to_string());
+            graph.get_mut(*b).unwrap().push(a.to_string());
+        }
+    }
+    for neighbors in graph.values_mut() {
+        neighbors.sort();
+    }
+    graph
+}
+
+
+// purpose: count, for every pair of keys, how many members they share
+// input: a map from each key to its set of members (e.g. video -> users)
+// output: a hashmap from (key1, key2), key1 < key2, to the # of members the two have in common
+// goes over each member's keys and counts every pair among them, so it only looks at pairs that share something
+fn shared_member_counts(members_of: &HashMap<String, HashSet<String>>) -> HashMap<(&String, &String), usize> {
+    let mut keys_of: HashMap<&String, Vec<&String>> = HashMap::new();
+    for (key, members) in members_of.iter() {
+        for member in members.iter() {
+            keys_of.entry(member).or_default().push(key);
+        }
+    }
+
+    let mut counts: HashMap<(&String, &String), usize> = HashMap::new();
+    for keys in keys_of.values_mut() {
+        keys.sort();
+        for i in 0..keys.len() {
+            for j in (i + 1)..keys.len() {
+                *counts.entry((keys[i], keys[j])).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+
+impl BipartiteGraph {
+
+    // purpose: build the bipartite graph from the data
+    // input: an array with cols 0-user and 2-video name
+    // output: a BipartiteGraph with an edge between every user and every video they commented on
+    pub fn build(arr_data: &Array2<crate::ColumnVal>) -> BipartiteGraph {
+        let mut bipartite = BipartiteGraph { user_videos: HashMap::new(), video_users: HashMap::new() };
+        for row in arr_data.rows() {
+            let user = row[0].as_text().unwrap_or("").to_string();
+            let video = row[2].as_text().unwrap_or("").to_string();
+            bipartite.user_videos.entry(user.clone()).or_default().insert(video.clone());
+            bipartite.video_users.entry(video).or_default().insert(user);
+        }
+        bipartite
+    }
+
+    // purpose: keep only some of the users (e.g. the spammers from find_spam)
+    // input: the users to keep
+    // output: a new BipartiteGraph with only those users, and only the videos at least one of them commented on
+    pub fn restrict_to(&self, users: &[String]) -> BipartiteGraph {
+        let mut bipartite = BipartiteGraph { user_videos: HashMap::new(), video_users: HashMap::new() };
+        for user in users.iter() {
+            if let Some(videos) = self.user_videos.get(user) {
+                bipartite.user_videos.insert(user.clone(), videos.clone());
+                for video in videos.iter() {
+                    bipartite.video_users.entry(video.clone()).or_default().insert(user.clone());
+                }
+            }
+        }
+        bipartite
+    }
+
+    // purpose: project the bipartite graph onto the users
+    // input: the min # of videos two users must share to be neighbors
+    // output: a graph mapping each user to the users sharing at least min_shared videos with them
+    pub fn project_users(&self, min_shared: usize) -> HashMap<String, Vec<String>> {
+        let counts = shared_member_counts(&self.user_videos);
+        graph_from_pair_counts(self.user_videos.keys().collect(), &counts, min_shared.max(1))
+    }
+
+    // purpose: project the bipartite graph onto the videos
+    // input: the min # of users two videos must share to be neighbors
+    // output: a graph mapping each video to the videos sharing at least min_shared users with them
+    pub fn project_videos(&self, min_shared: usize) -> HashMap<String, Vec<String>> {
+        let counts = shared_member_counts(&self.video_users);
+        graph_from_pair_counts(self.video_users.keys().collect(), &counts, min_shared.max(1))
+    }
+
+    // purpose: find how much each pair of videos' audiences overlap
+    // input: the bipartite graph
+    // output: a vector of (video1, video2, # of shared users) for every pair sharing at least one user, most shared first
+    pub fn video_overlap(&self) -> Vec<(String, String, usize)> {
+        let mut overlap: Vec<(String, String, usize)> = shared_member_counts(&self.video_users).into_iter()
+            .map(|((a, b), count)| (a.clone(), b.clone(), count))
+            .collect();
+        overlap.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| (&a.0, &a.1).cmp(&(&b.0, &b.1))));
+        overlap
+    }
+
+    // purpose: group users that commented on exactly the same set of videos
+    // input: the bipartite graph
+    // output: a vector of (sorted video set, sorted users who hit exactly that set), biggest groups first
+    pub fn groups_by_video_set(&self) -> Vec<(Vec<String>, Vec<String>)> {
+        let mut groups: HashMap<Vec<String>, Vec<String>> = HashMap::new();
+        for (user, videos) in self.user_videos.iter() {
+            let mut video_set: Vec<String> = videos.iter().cloned().collect();
+            video_set.sort();
+            groups.entry(video_set).or_default().push(user.clone());
+        }
+        let mut out: Vec<(Vec<String>, Vec<String>)> = groups.into_iter().collect();
+        for (_, users) in out.iter_mut() {
+            users.sort();
+        }
+        out.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+        out
+    }
+}
@@ -0,0 +1,185 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Module summary: a report of users that look like they are run by the same operator (sockpuppets). It combines the
+// vocabulary similarity graph with shared videos and posting times, and gives the evidence behind every pair and group
+// instead of just one degree-maximal set like find_best_spammer().
+
+
+// It is the evidence that two users are coordinated
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairEvidence {
+    pub user1: String, // user1 < user2
+    pub user2: String,
+    pub similarity: f64, // Jaccard similarity of their vocabularies, as in find_similarities()
+    pub shared_rare_tokens: Vec<(String, f64)>, // shared tokens used by few users, with their IDF, rarest first
+    pub shared_videos: Vec<String>, // sorted
+    pub close_posts: usize, // # of (comment by user1, comment by user2) pairs posted within the time window
+    pub score: f64, // mean of the four evidence scores, from 0 to 1
+}
+
+
+// It is a group of users linked by suspicious pairs
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupEvidence {
+    pub users: Vec<String>, // sorted
+    pub num_pairs: usize, // # of suspicious pairs inside the group
+    pub mean_score: f64,
+    pub videos: Vec<(String, usize)>, // videos hit by 2+ members, with their # of members, most first
+    pub rare_tokens: Vec<(String, usize)>, // rare tokens used by 2+ members, with their # of members, most first
+}
+
+
+// It holds the ranked pairs and groups
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoordinationReport {
+    pub pairs: Vec<PairEvidence>, // highest score first
+    pub groups: Vec<GroupEvidence>, // biggest first
+}
+
+
+// purpose: find pairs of different users that posted within a time window of each other
+// input: every dated comment as (timestamp, user), sorted by time, and the window in seconds
+// output: a hashmap from (user1, user2), user1 < user2, to their # of comment pairs within the window
+fn close_post_pairs(posts: &[(i64, String)], window_secs: i64) -> HashMap<(String, String), usize> {
+    let mut pairs: HashMap<(String, String), usize> = HashMap::new();
+    for i in 0..posts.len() {
+        for j in (i + 1)..posts.len() {
+            if posts[j].0 - posts[i].0 > window_secs {
+                break;
+            }
+            if posts[i].1 != posts[j].1 {
+                let key = if posts[i].1 < posts[j].1 { (posts[i].1.clone(), posts[j].1.clone()) } else { (posts[j].1.clone(), posts[i].1.clone()) };
+                *pairs.entry(key).or_insert(0) += 1;
+            }
+        }
+    }
+    pairs
+}
+
+
+// purpose: rank the users most likely to be coordinated
+// input: the array of data (col 4 holds the date), the similarity graph and the user -> words map it was built from,
+// the time window in minutes, the max # of users a token may have to count as rare, and the min score to report a pair
+// output: a CoordinationReport
+// candidate pairs are the edges of the graph plus every pair that posted within the window of each other. Each
+// candidate gets four scores from 0 to 1: its vocabulary similarity, the IDF-weighted similarity of its vocabularies
+// (rare shared tokens count more), the Jaccard similarity of its video sets, and its close posts (capped at 3). The
+// pair score is their mean. Groups are the connected subgraphs of 3+ users when only the reported pairs are kept
+pub fn coordination_report(arr_data: &Array2<crate::ColumnVal>, graph: &HashMap<String, Vec<String>>, map: &HashMap<String, HashSet<String>>,
+    window_minutes: i64, max_token_users: usize, min_score: f64) -> CoordinationReport {
+    let idf = crate::text_functions::user_idf(map);
+    let token_users = crate::text_functions::token_user_counts(map);
+    let bipartite = crate::bipartite_functions::BipartiteGraph::build(arr_data);
+
+    let mut posts: Vec<(i64, String)> = (0..arr_data.nrows())
+        .filter_map(|i| crate::time_functions::row_timestamp(arr_data, i).map(|t| (t, arr_data[[i, 0]].as_text().unwrap_or("").to_string())))
+        .collect();
+    posts.sort();
+    let close_posts = close_post_pairs(&posts, window_minutes * 60);
+
+    let mut candidates: HashSet<(String, String)> = close_posts.keys().cloned().collect();
+    for (user, neighbors) in graph.iter() {
+        for neighbor in neighbors.iter().filter(|n| *user < **n) {
+            candidates.insert((user.clone(), neighbor.clone()));
+        }
+    }
+
+    let empty: HashSet<String> = HashSet::new();
+    let mut pairs: Vec<PairEvidence> = Vec::new();
+    for (user1, user2) in candidates.into_iter() {
+        let words1 = map.get(&user1).unwrap_or(&empty);
+        let words2 = map.get(&user2).unwrap_or(&empty);
+        let weight = |token: &String| idf.get(token).copied().unwrap_or(0.0);
+        let shared_weight: f64 = words1.intersection(words2).map(weight).sum();
+        let total_weight: f64 = words1.union(words2).map(weight).sum();
+        let similarity = crate::find_similarities(user1.clone(), user2.clone(), map).filter(|s| s.is_finite()).unwrap_or(0.0);
+        let weighted_similarity = if total_weight > 0.0 { shared_weight / total_weight } else { 0.0 };
+
+        let videos1 = bipartite.user_videos.get(&user1).unwrap_or(&empty);
+        let videos2 = bipartite.user_videos.get(&user2).unwrap_or(&empty);
+        let mut shared_videos: Vec<String> = videos1.intersection(videos2).cloned().collect();
+        shared_videos.sort();
+        let video_overlap = shared_videos.len() as f64 / videos1.union(videos2).count().max(1) as f64;
+
+        let num_close = close_posts.get(&(user1.clone(), user2.clone())).copied().unwrap_or(0);
+        let score = (similarity + weighted_similarity + video_overlap + num_close.min(3) as f64 / 3.0) / 4.0;
+        if score < min_score {
+            continue;
+        }
+
+        let mut shared_rare_tokens: Vec<(String, f64)> = words1.intersection(words2)
+            .filter(|token| token_users.get(*token).is_some_and(|count| *count <= max_token_users))
+            .map(|token| (token.clone(), weight(token)))
+            .collect();
+        shared_rare_tokens.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        pairs.push(PairEvidence { user1, user2, similarity, shared_rare_tokens, shared_videos, close_posts: num_close, score });
+    }
+    pairs.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| (&a.user1, &a.user2).cmp(&(&b.user1, &b.user2))));
+
+    let mut pair_graph: HashMap<String, Vec<String>> = HashMap::new();
+    for pair in pairs.iter() {
+        pair_graph.entry(pair.user1.clone()).or_default().push(pair.user2.clone());
+        pair_graph.entry(pair.user2.clone()).or_default().push(pair.user1.clone());
+    }
+    let mut members: HashMap<u32, Vec<String>> = HashMap::new();
+    for (user, id) in crate::find_communities(&pair_graph) {
+        members.entry(id).or_default().push(user);
+    }
+
+    let mut groups: Vec<GroupEvidence> = Vec::new();
+    for mut users in members.into_values().filter(|m| m.len() >= 3) {
+        users.sort();
+        let member_set: HashSet<&String> = users.iter().collect();
+        let inside: Vec<&PairEvidence> = pairs.iter().filter(|p| member_set.contains(&p.user1)).collect();
+
+        let mut video_counts: HashMap<String, usize> = HashMap::new();
+        let mut token_counts: HashMap<String, usize> = HashMap::new();
+        for user in users.iter() {
+            for video in bipartite.user_videos.get(user).unwrap_or(&empty) {
+                *video_counts.entry(video.clone()).or_insert(0) += 1;
+            }
+            for token in map.get(user).unwrap_or(&empty).iter().filter(|t| token_users.get(*t).is_some_and(|count| *count <= max_token_users)) {
+                *token_counts.entry(token.clone()).or_insert(0) += 1;
+            }
+        }
+        let most_shared = |counts: HashMap<String, usize>| -> Vec<(String, usize)> {
+            let mut out: Vec<(String, usize)> = counts.into_iter().filter(|(_, count)| *count >= 2).collect();
+            out.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            out
+        };
+
+        groups.push(GroupEvidence {
+            num_pairs: inside.len(),
+            mean_score: inside.iter().map(|p| p.score).sum::<f64>() / inside.len() as f64,
+            videos: most_shared(video_counts),
+            rare_tokens: most_shared(token_counts),
+            users,
+        });
+    }
+    groups.sort_by(|a, b| b.users.len().cmp(&a.users.len()).then_with(|| b.mean_score.total_cmp(&a.mean_score)).then_with(|| a.users.cmp(&b.users)));
+
+    CoordinationReport { pairs, groups }
+}
+
+
+impl CoordinationReport {
+
+    // purpose: print the top pairs and groups with their evidence
+    // input: how many pairs and groups to show
+    // output: none, prints to the terminal
+    pub fn print(&self, top_n: usize) {
+        println!("Suspected coordinated accounts: {} pairs, {} groups of 3+", self.pairs.len(), self.groups.len());
+        for pair in self.pairs.iter().take(top_n) {
+            let rare: Vec<&String> = pair.shared_rare_tokens.iter().take(5).map(|(token, _)| token).collect();
+            println!("  {:?} & {:?}: score {:.3}, similarity {:.3}, {} shared videos, {} close posts, shared rare tokens {:?}",
+                pair.user1, pair.user2, pair.score, pair.similarity, pair.shared_videos.len(), pair.close_posts, rare);
+        }
+        for group in self.groups.iter().take(top_n) {
+            println!("  group of {} ({} pairs, mean score {:.3}): {:?}", group.users.len(), group.num_pairs, group.mean_score,
+                group.users.iter().take(6).collect::<Vec<_>>());
+            println!("    videos {:?}, rare tokens {:?}", group.videos.iter().take(3).collect::<Vec<_>>(), group.rare_tokens.iter().take(5).collect::<Vec<_>>());
+        }
+    }
+}
@@ -1,11 +1,12 @@
 use ndarray::{Array, Array2};
 use csv::ReaderBuilder;
+use std::collections::HashSet;
 
-// Module summary: these are the two functions that read in the CSV and return arrays.
+// Module summary: these are the two functions that read in the CSV and return arrays, and a pass that drops duplicate rows.
 
 // purpose: put the relevant CSV data into an array
 // input: a path with the CSV name
-// output: an Array2 of ColumnVals representing the relevant data
+// output: an Array2 of ColumnVals representing the relevant data. Cols: 0-user, 1-content, 2-video name, 3-class, 4-date, 5-comment id
 // iterate over the lines of the CSV, split by commas but treat things in double quotes as single entries. Gets only the cols of interest
 pub fn read_CSV_using_reader(path: &str) -> Array2<crate::ColumnVal> {
     let mut rdr = csv::ReaderBuilder::new()
@@ -41,6 +42,9 @@ pub fn read_CSV_using_reader(path: &str) -> Array2<crate::ColumnVal> {
                         
                     }
                 }
+                // the date (CSV col 2) and the comment id (CSV col 0) go last, so the first four cols stay where they always were. Some rows have no date
+                giant_vec.push(crate::ColumnVal::One(record.get(2).unwrap_or("").to_string()));
+                giant_vec.push(crate::ColumnVal::One(record.get(0).unwrap_or("").to_string()));
             },
             Err(err) => {
                 println!("error reading CSV record {}", err);
@@ -48,7 +52,7 @@ pub fn read_CSV_using_reader(path: &str) -> Array2<crate::ColumnVal> {
         }
     }
     
-    let out_arr: Array2<crate::ColumnVal> = Array::from_vec(giant_vec).into_shape((counter, 4)).expect("Failed to reshape!");
+    let out_arr: Array2<crate::ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((counter, 6)).expect("Failed to reshape!");
     return out_arr;
 }
 
@@ -56,8 +60,8 @@ pub fn read_CSV_using_reader(path: &str) -> Array2<crate::ColumnVal> {
 
 // purpose: a duplicate of the read_CSV_using_reader() function, except it filters out the rows that correspond to non-spam content.
 // input: a &str that is the name of the CSV to load in
-// output: an Array2 of the data that correspond to rows (comments and their data) classified as spam
-// iterates over rdr.records() and matches each to either a valid line or an error reading the line. Takes the columns of interest: 1, 3, 4, 5
+// output: an Array2 of the data that correspond to rows (comments and their data) classified as spam, with the same cols as read_CSV_using_reader()
+// iterates over rdr.records() and matches each to either a valid line or an error reading the line. Takes the columns of interest: 1, 3, 4, 5, then 2 and 0
 pub fn spam_specific_arr(path: &str) -> Array2<crate::ColumnVal> {
     let mut rdr = csv::ReaderBuilder::new()
     .has_headers(true)
@@ -85,6 +89,8 @@ pub fn spam_specific_arr(path: &str) -> Array2<crate::ColumnVal> {
                         if item == "1" {
                             counter += 1;
                             giant_vec.push(crate::ColumnVal::Two(true));
+                            giant_vec.push(crate::ColumnVal::One(record.get(2).unwrap_or("").to_string())); // then the date
+                            giant_vec.push(crate::ColumnVal::One(record.get(0).unwrap_or("").to_string())); // and the comment id
                         } else if item == "0" {
                             // remove those three your just pushed in, since this person is not a spammer
                             giant_vec.pop();
@@ -103,6 +109,94 @@ pub fn spam_specific_arr(path: &str) -> Array2<crate::ColumnVal> {
         }
     }
     
-    let out_arr: Array2<crate::ColumnVal> = Array::from_vec(giant_vec).into_shape((counter, 4)).expect("Failed to reshape!");
+    let out_arr: Array2<crate::ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((counter, 6)).expect("Failed to reshape!");
     return out_arr;
 }
+
+
+
+// what makes two rows duplicates of each other
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DedupKey {
+    CommentId, // the same COMMENT_ID (col 5), e.g. from overlapping scrapes
+    AuthorContent, // the same author posting the exact same text
+}
+
+
+// It says what a deduplication pass did
+#[derive(Debug, Clone, PartialEq)]
+pub struct DedupReport {
+    pub key: DedupKey,
+    pub kept: usize,
+    pub dropped: usize,
+    pub dropped_rows: Vec<usize>, // row indices into the input array, ascending
+}
+
+
+// purpose: drop repeated rows
+// input: the array of data and what makes two rows duplicates
+// output: Ok((a new array keeping only the first row of each key, in the original order, and a DedupReport)), or
+// Err if the key is CommentId and the array has no comment id column
+// AuthorContent keys rows by the author and the full content, so two different comments are never taken for each
+// other. Rows with an empty comment id are never dropped by CommentId
+pub fn deduplicate(arr_data: &Array2<crate::ColumnVal>, key: DedupKey) -> Result<(Array2<crate::ColumnVal>, DedupReport), String> {
+    if key == DedupKey::CommentId && arr_data.ncols() <= 5 {
+        return Err("the data has no comment id column (col 5)".to_string());
+    }
+
+    let mut seen: HashSet<(String, String)> = HashSet::new();
+    let mut kept_rows: Vec<usize> = Vec::new();
+    let mut dropped_rows: Vec<usize> = Vec::new();
+    for i in 0..arr_data.nrows() {
+        let row_key = match key {
+            DedupKey::CommentId => (arr_data[[i, 5]].as_text().unwrap_or("").to_string(), String::new()),
+            DedupKey::AuthorContent => (arr_data[[i, 0]].as_text().unwrap_or("").to_string(), arr_data[[i, 1]].as_text().unwrap_or("").to_string()),
+        };
+        if (key == DedupKey::CommentId && row_key.0.is_empty()) || seen.insert(row_key) {
+            kept_rows.push(i);
+        } else {
+            dropped_rows.push(i);
+        }
+    }
+
+    let report = DedupReport { key, kept: kept_rows.len(), dropped: dropped_rows.len(), dropped_rows };
+    Ok((crate::split_functions::select_rows(arr_data, &kept_rows), report))
+}
+
+
+// purpose: read which deduplication passes to run, as given on the command line
+// input: a comma-separated list of "id" (DedupKey::CommentId), "author-content" (DedupKey::AuthorContent) or just "none"
+// output: Ok(the keys, each once, in the order given), or Err naming the first word that is not one of these
+pub fn parse_dedup_keys(text: &str) -> Result<Vec<DedupKey>, String> {
+    let mut keys: Vec<DedupKey> = Vec::new();
+    for word in text.split(',').map(|word| word.trim()) {
+        let key = match word {
+            "id" => DedupKey::CommentId,
+            "author-content" => DedupKey::AuthorContent,
+            "none" if text.trim() == "none" => continue,
+            _ => return Err(format!("unknown deduplication key {:?} (expected id, author-content or none)", word)),
+        };
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    Ok(keys)
+}
+
+
+// purpose: fingerprint the data, to tell whether something saved from it earlier is still up to date
+// input: the array of data
+// output: a 64-bit FNV-1a hash of every row's user, content and class (cols 0, 1 and 3). Unlike the std hasher it is
+// fixed, so a hash saved to a file can be compared on a later run
+pub fn data_hash(arr_data: &Array2<crate::ColumnVal>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for i in 0..arr_data.nrows() {
+        for col in [0, 1, 3] {
+            for byte in arr_data[[i, col]].to_key().bytes().chain([0xff]) { // 0xff never occurs in UTF-8, so it separates the cells
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+    }
+    hash
+}
@@ -0,0 +1,170 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Module summary: near-duplicate detection at the comment level. Each comment becomes a set of character shingles,
+// MinHash signatures and locality-sensitive hashing (LSH) pick the candidate pairs without comparing every pair, and
+// the candidates that really are similar are joined into clusters. A cluster posted by several authors is a
+// copy-paste campaign.
+
+
+// It is a group of comments whose texts are near-duplicates of each other
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateCluster {
+    pub rows: Vec<usize>, // row indices into the array, ascending
+    pub comment_ids: Vec<String>, // in the same order as rows
+    pub authors: Vec<String>, // distinct, sorted
+    pub videos: Vec<String>, // distinct, sorted
+}
+
+
+// purpose: read the comment id of one row
+// input: the array of data (col 5 holds the COMMENT_ID) and a row index
+// output: the comment id, or "row <index>" when the array has no comment id column
+pub fn row_comment_id(arr_data: &Array2<crate::ColumnVal>, row: usize) -> String {
+    if arr_data.ncols() <= 5 {
+        return format!("row {}", row);
+    }
+    arr_data[[row, 5]].as_text().unwrap_or("").to_string()
+}
+
+
+// purpose: turn a comment into character shingles
+// input: the comment content and the shingle length in characters
+// output: the set of every shingle_len-character substring of the tokenized comment (tokens joined by single spaces).
+// A comment shorter than one shingle is a single shingle, and an empty comment has none
+pub fn shingles(content: &str, shingle_len: usize) -> HashSet<String> {
+    let chars: Vec<char> = crate::text_functions::tokenize(content).join(" ").chars().collect();
+    let shingle_len = shingle_len.max(1);
+    if chars.is_empty() {
+        return HashSet::new();
+    }
+    if chars.len() <= shingle_len {
+        return HashSet::from([chars.into_iter().collect()]);
+    }
+    chars.windows(shingle_len).map(|w| w.iter().collect()).collect()
+}
+
+
+// FNV-1a hash of a string. Unlike the std hasher it is fixed, so signatures are the same on every run and platform
+fn fnv1a(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+
+// the splitmix64 finalizer, used to derive many independent hash functions from one hash
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+
+// purpose: compute the MinHash signature of a shingle set
+// input: the shingles and the # of hash functions
+// output: a vector with, for each hash function, the smallest hash of any shingle. Two sets agree in a given position
+// with probability equal to their Jaccard similarity
+pub fn minhash_signature(shingle_set: &HashSet<String>, num_hashes: usize) -> Vec<u64> {
+    let base: Vec<u64> = shingle_set.iter().map(|s| fnv1a(s)).collect();
+    (0..num_hashes as u64).map(|h| {
+        let salt = mix(h);
+        base.iter().map(|b| mix(b ^ salt)).min().unwrap_or(u64::MAX)
+    }).collect()
+}
+
+
+// purpose: find the root of an element in a union-find forest, flattening the path on the way
+fn find_root(parent: &mut [usize], mut x: usize) -> usize {
+    while parent[x] != x {
+        parent[x] = parent[parent[x]];
+        x = parent[x];
+    }
+    x
+}
+
+
+// purpose: cluster near-duplicate comments
+// input: the array of data, the shingle length in characters, the # of LSH bands and rows per band (the signatures
+// have bands * rows_per_band hashes), and the min Jaccard similarity of the shingle sets for two comments to count as
+// near-duplicates
+// output: a vector of DuplicateClusters with at least two comments, the biggest first (ties by first row)
+// comments whose signatures agree on every hash of some band land in the same bucket and become candidates; only
+// candidates whose exact shingle Jaccard similarity reaches the threshold are joined, with union-find, so a cluster is
+// a connected group of near-duplicate pairs. More bands find more candidates, more rows per band fewer
+pub fn find_near_duplicates(arr_data: &Array2<crate::ColumnVal>, shingle_len: usize, num_bands: usize, rows_per_band: usize, threshold: f64) -> Vec<DuplicateCluster> {
+    let num_rows = arr_data.nrows();
+    let rows_per_band = rows_per_band.max(1);
+    let shingle_sets: Vec<HashSet<String>> = (0..num_rows).map(|i| shingles(arr_data[[i, 1]].as_text().unwrap_or(""), shingle_len)).collect();
+
+    let mut buckets: HashMap<(usize, Vec<u64>), Vec<usize>> = HashMap::new();
+    for (i, shingle_set) in shingle_sets.iter().enumerate() {
+        if shingle_set.is_empty() {
+            continue;
+        }
+        let signature = minhash_signature(shingle_set, num_bands * rows_per_band);
+        for (band, band_hashes) in signature.chunks(rows_per_band).enumerate() {
+            buckets.entry((band, band_hashes.to_vec())).or_default().push(i);
+        }
+    }
+
+    let mut parent: Vec<usize> = (0..num_rows).collect();
+    let mut checked: HashSet<(usize, usize)> = HashSet::new();
+    for members in buckets.values() {
+        for a in 0..members.len() {
+            for b in (a + 1)..members.len() {
+                let (i, j) = (members[a], members[b]);
+                if !checked.insert((i, j)) {
+                    continue;
+                }
+                let shared = shingle_sets[i].intersection(&shingle_sets[j]).count();
+                let union = shingle_sets[i].len() + shingle_sets[j].len() - shared;
+                if shared as f64 / union as f64 >= threshold {
+                    let (root_i, root_j) = (find_root(&mut parent, i), find_root(&mut parent, j));
+                    parent[root_i.max(root_j)] = root_i.min(root_j);
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..num_rows {
+        let root = find_root(&mut parent, i);
+        groups.entry(root).or_default().push(i);
+    }
+    let mut clusters: Vec<DuplicateCluster> = groups.into_values().filter(|rows| rows.len() >= 2).map(|rows| {
+        let mut authors: Vec<String> = rows.iter().map(|i| arr_data[[*i, 0]].as_text().unwrap_or("").to_string()).collect::<HashSet<String>>().into_iter().collect();
+        let mut videos: Vec<String> = rows.iter().map(|i| arr_data[[*i, 2]].as_text().unwrap_or("").to_string()).collect::<HashSet<String>>().into_iter().collect();
+        authors.sort();
+        videos.sort();
+        DuplicateCluster { comment_ids: rows.iter().map(|i| row_comment_id(arr_data, *i)).collect(), rows, authors, videos }
+    }).collect();
+    clusters.sort_by(|a, b| b.rows.len().cmp(&a.rows.len()).then_with(|| a.rows[0].cmp(&b.rows[0])));
+    clusters
+}
+
+
+// purpose: write the clusters to a CSV file for review
+// input: the path to write, the array of data the clusters came from, and the clusters
+// output: an io Result. The file has a header row and one row per comment: cluster #, comment id, author, video, content
+pub fn write_duplicate_clusters(path: &str, arr_data: &Array2<crate::ColumnVal>, clusters: &[DuplicateCluster]) -> std::io::Result<()> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(["cluster", "comment_id", "author", "video", "content"])?;
+    for (num, cluster) in clusters.iter().enumerate() {
+        for (row, comment_id) in cluster.rows.iter().zip(cluster.comment_ids.iter()) {
+            wtr.write_record([
+                num.to_string().as_str(),
+                comment_id,
+                arr_data[[*row, 0]].as_text().unwrap_or(""),
+                arr_data[[*row, 2]].as_text().unwrap_or(""),
+                arr_data[[*row, 1]].as_text().unwrap_or(""),
+            ])?;
+        }
+    }
+    wtr.flush()
+}
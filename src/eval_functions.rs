@@ -0,0 +1,243 @@
+use ndarray::{Array2, ArrayView1};
+
+// Module summary: functions for scoring predictions against the CLASS labels (col 3). Any scorer that gives a row of the
+// comment table a spam score can be evaluated here, so classifiers and graph heuristics are compared on the same footing.
+
+
+// It counts how the predictions line up with the real labels. Spam is the positive class
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ConfusionMatrix {
+    pub true_pos: u32,
+    pub false_pos: u32,
+    pub true_neg: u32,
+    pub false_neg: u32,
+}
+
+// divides two counts, giving 0 instead of NaN when there is nothing to divide by
+fn ratio(numerator: u32, denominator: u32) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+impl ConfusionMatrix {
+
+    // purpose: count the four outcomes of a set of predictions
+    // input: the predicted labels and the real labels (same length, true = spam)
+    // output: the filled-in ConfusionMatrix
+    pub fn from_predictions(predicted: &[bool], actual: &[bool]) -> ConfusionMatrix {
+        let mut matrix = ConfusionMatrix::default();
+        for (p, a) in predicted.iter().zip(actual.iter()) {
+            match (*p, *a) {
+                (true, true) => matrix.true_pos += 1,
+                (true, false) => matrix.false_pos += 1,
+                (false, false) => matrix.true_neg += 1,
+                (false, true) => matrix.false_neg += 1,
+            }
+        }
+        matrix
+    }
+
+    // fraction of all predictions that were right
+    pub fn accuracy(&self) -> f64 {
+        ratio(self.true_pos + self.true_neg, self.true_pos + self.false_pos + self.true_neg + self.false_neg)
+    }
+
+    // fraction of comments predicted spam that really were spam
+    pub fn precision(&self) -> f64 {
+        ratio(self.true_pos, self.true_pos + self.false_pos)
+    }
+
+    // fraction of real spam comments that were predicted spam
+    pub fn recall(&self) -> f64 {
+        ratio(self.true_pos, self.true_pos + self.false_neg)
+    }
+
+    // harmonic mean of precision and recall
+    pub fn f1(&self) -> f64 {
+        let (p, r) = (self.precision(), self.recall());
+        if p + r == 0.0 {
+            0.0
+        } else {
+            2.0 * p * r / (p + r)
+        }
+    }
+}
+
+
+// purpose: sort scores from highest to lowest and find the cumulative true/false positive counts at each distinct score
+// input: the scores and the real labels
+// output: a vector of (true positives, false positives) counts, one entry per distinct score, plus the totals of
+// positives and negatives. Tied scores are treated as one threshold, so the curves don't depend on the order of ties
+fn cumulative_counts(scores: &[f64], labels: &[bool]) -> (Vec<(u32, u32)>, u32, u32) {
+    let mut pairs: Vec<(f64, bool)> = scores.iter().copied().zip(labels.iter().copied()).collect();
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut counts: Vec<(u32, u32)> = Vec::new();
+    let (mut tp, mut fp) = (0, 0);
+    for (num, (score, label)) in pairs.iter().enumerate() {
+        if *label {
+            tp += 1;
+        } else {
+            fp += 1;
+        }
+        if num + 1 == pairs.len() || pairs[num + 1].0 != *score { // last of a run of tied scores
+            counts.push((tp, fp));
+        }
+    }
+    (counts, tp, fp)
+}
+
+
+// purpose: compute the ROC curve
+// input: the scores (higher = more likely spam) and the real labels
+// output: a vector of (false positive rate, true positive rate) points from (0, 0) to (1, 1)
+pub fn roc_curve(scores: &[f64], labels: &[bool]) -> Vec<(f64, f64)> {
+    let (counts, total_pos, total_neg) = cumulative_counts(scores, labels);
+    let mut curve = vec![(0.0, 0.0)];
+    for (tp, fp) in counts {
+        curve.push((ratio(fp, total_neg), ratio(tp, total_pos)));
+    }
+    curve
+}
+
+
+// purpose: compute the precision-recall curve
+// input: the scores (higher = more likely spam) and the real labels
+// output: a vector of (recall, precision) points, starting at recall 0 with precision 1
+pub fn pr_curve(scores: &[f64], labels: &[bool]) -> Vec<(f64, f64)> {
+    let (counts, total_pos, _) = cumulative_counts(scores, labels);
+    let mut curve = vec![(0.0, 1.0)];
+    for (tp, fp) in counts {
+        curve.push((ratio(tp, total_pos), ratio(tp, tp + fp)));
+    }
+    curve
+}
+
+
+// purpose: find the area under a curve
+// input: a vector of (x, y) points sorted by x
+// output: the area, using the trapezoid rule between consecutive points
+pub fn auc(curve: &[(f64, f64)]) -> f64 {
+    curve.windows(2)
+        .map(|pair| (pair[1].0 - pair[0].0) * (pair[1].1 + pair[0].1) / 2.0)
+        .sum()
+}
+
+
+// purpose: find the area under a precision-recall curve
+// input: a vector of (recall, precision) points from pr_curve()
+// output: the average precision. It uses steps instead of trapezoids, since interpolating linearly between PR points is
+// too optimistic
+pub fn average_precision(curve: &[(f64, f64)]) -> f64 {
+    curve.windows(2)
+        .map(|pair| (pair[1].0 - pair[0].0) * pair[1].1)
+        .sum()
+}
+
+
+// It is one bin of a calibration table: the comments whose score fell in [lower, upper)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: u32,
+    pub mean_score: f64,
+    pub spam_rate: f64,
+}
+
+
+// purpose: check how well scores match real spam rates
+// input: the scores (between 0 and 1), the real labels, and the # of equal-width bins
+// output: a vector of num_bins CalibrationBins. For a well calibrated scorer, mean_score and spam_rate are close in every bin
+// puts each score in its bin (a score of exactly 1 goes in the last one) and averages scores and labels per bin
+pub fn calibration_bins(scores: &[f64], labels: &[bool], num_bins: usize) -> Vec<CalibrationBin> {
+    let num_bins = num_bins.max(1);
+    let mut counts = vec![0u32; num_bins];
+    let mut score_sums = vec![0.0; num_bins];
+    let mut spam_counts = vec![0u32; num_bins];
+
+    for (score, label) in scores.iter().zip(labels.iter()) {
+        let b = ((score.clamp(0.0, 1.0) * num_bins as f64) as usize).min(num_bins - 1);
+        counts[b] += 1;
+        score_sums[b] += score;
+        if *label {
+            spam_counts[b] += 1;
+        }
+    }
+
+    (0..num_bins).map(|b| CalibrationBin {
+        lower: b as f64 / num_bins as f64,
+        upper: (b + 1) as f64 / num_bins as f64,
+        count: counts[b],
+        mean_score: if counts[b] == 0 { 0.0 } else { score_sums[b] / counts[b] as f64 },
+        spam_rate: ratio(spam_counts[b], counts[b]),
+    }).collect()
+}
+
+
+// It gathers every metric for one scorer on one labeled table
+#[derive(Debug, Clone)]
+pub struct EvalReport {
+    pub confusion: ConfusionMatrix,
+    pub roc: Vec<(f64, f64)>,
+    pub roc_auc: f64,
+    pub pr: Vec<(f64, f64)>,
+    pub pr_auc: f64,
+    pub calibration: Vec<CalibrationBin>,
+}
+
+impl EvalReport {
+    // prints the headline numbers and the calibration table
+    pub fn print(&self, name: &str) {
+        println!("Evaluation of {}:", name);
+        println!("Confusion matrix: TP {} FP {} TN {} FN {}", self.confusion.true_pos, self.confusion.false_pos, self.confusion.true_neg, self.confusion.false_neg);
+        println!("Accuracy {:.3}, precision {:.3}, recall {:.3}, F1 {:.3}", self.confusion.accuracy(), self.confusion.precision(), self.confusion.recall(), self.confusion.f1());
+        println!("ROC AUC {:.3}, PR AUC (average precision) {:.3}", self.roc_auc, self.pr_auc);
+        for bin in self.calibration.iter().filter(|bin| bin.count > 0) {
+            println!("  scores [{:.1}, {:.1}): {} scored, mean score {:.3}, spam rate {:.3}", bin.lower, bin.upper, bin.count, bin.mean_score, bin.spam_rate);
+        }
+    }
+}
+
+
+// purpose: read the real labels of a table
+// input: an array whose col 3 holds the spam labels
+// output: a vector with one bool per row
+pub fn labels_of(arr_data: &Array2<crate::ColumnVal>) -> Vec<bool> {
+    arr_data.column(3).iter().map(|cell| cell.as_bool().unwrap_or(false)).collect()
+}
+
+
+// purpose: compute every metric for a set of scores
+// input: the scores (higher = more likely spam), the real labels, the score at or above which something counts as
+// predicted spam, and the # of calibration bins
+// output: an EvalReport with every metric
+pub fn evaluate_scores(scores: &[f64], labels: &[bool], threshold: f64, num_bins: usize) -> EvalReport {
+    let predicted: Vec<bool> = scores.iter().map(|s| *s >= threshold).collect();
+    let roc = roc_curve(scores, labels);
+    let pr = pr_curve(scores, labels);
+    EvalReport {
+        confusion: ConfusionMatrix::from_predictions(&predicted, labels),
+        roc_auc: auc(&roc),
+        pr_auc: average_precision(&pr),
+        roc,
+        pr,
+        calibration: calibration_bins(scores, labels, num_bins),
+    }
+}
+
+
+// purpose: evaluate any scorer on a labeled table
+// input: the labeled array, a function giving each row a spam score, the score at or above which a row counts as
+// predicted spam, and the # of calibration bins
+// output: an EvalReport with every metric
+pub fn evaluate_scorer<F>(arr_data: &Array2<crate::ColumnVal>, scorer: F, threshold: f64, num_bins: usize) -> EvalReport
+where
+    F: Fn(ArrayView1<crate::ColumnVal>) -> f64,
+{
+    let scores: Vec<f64> = arr_data.rows().into_iter().map(scorer).collect();
+    evaluate_scores(&scores, &labels_of(arr_data), threshold, num_bins)
+}
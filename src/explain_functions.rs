@@ -0,0 +1,123 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Module summary: explains why two users are connected in the similarity graph. find_similarities() only returns a
+// number; this breaks it down into the tokens behind it and the comments they came from, so a moderator can justify
+// a ban decision.
+
+
+// It is one shared token and how much it contributes
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenContribution {
+    pub token: String,
+    pub idf: f64, // ln(# users / # users who used the token), so rare tokens weigh more
+    pub contribution: f64, // idf / the summed idf of every token either user used. These add up to the IDF-weighted similarity
+}
+
+
+// It is a comment by one of the two users that contains shared tokens
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExplainedComment {
+    pub row: usize,
+    pub comment_id: String,
+    pub user: String,
+    pub content: String,
+    pub shared_tokens: Vec<String>, // the shared tokens in this comment, sorted
+}
+
+
+// It is the full explanation of a user pair
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairExplanation {
+    pub user1: String,
+    pub user2: String,
+    pub similarity: f64, // the Jaccard similarity find_similarities() gives, what create_graph() compares to the threshold
+    pub weighted_similarity: f64, // the same with every token weighed by its idf
+    pub shared_tokens: Vec<TokenContribution>, // biggest contribution first
+    pub only_user1: Vec<String>, // sorted
+    pub only_user2: Vec<String>, // sorted
+    pub comments: Vec<ExplainedComment>, // in row order
+}
+
+
+// purpose: explain the similarity of two users
+// input: the array of data, the user -> words map from map_users_to_words(), and the two users' names
+// output: Ok(a PairExplanation) or Err(which user was not found)
+// the empty token map_users_to_words() can produce counts in the similarity but is left out of the token lists
+pub fn explain_pair(arr_data: &Array2<crate::ColumnVal>, map: &HashMap<String, HashSet<String>>, user1: &str, user2: &str) -> Result<PairExplanation, String> {
+    let words1 = map.get(user1).ok_or(format!("user {:?} not found", user1))?;
+    let words2 = map.get(user2).ok_or(format!("user {:?} not found", user2))?;
+    let idf = crate::text_functions::user_idf(map);
+    let weight = |token: &String| idf.get(token).copied().unwrap_or(0.0);
+
+    let total_weight: f64 = words1.union(words2).map(weight).sum();
+    let mut shared_tokens: Vec<TokenContribution> = words1.intersection(words2).filter(|t| !t.is_empty()).map(|token| TokenContribution {
+        token: token.clone(),
+        idf: weight(token),
+        contribution: if total_weight > 0.0 { weight(token) / total_weight } else { 0.0 },
+    }).collect();
+    shared_tokens.sort_by(|a, b| b.contribution.total_cmp(&a.contribution).then_with(|| a.token.cmp(&b.token)));
+
+    let only = |mine: &HashSet<String>, theirs: &HashSet<String>| -> Vec<String> {
+        let mut out: Vec<String> = mine.difference(theirs).filter(|t| !t.is_empty()).cloned().collect();
+        out.sort();
+        out
+    };
+
+    let shared_set: HashSet<&String> = shared_tokens.iter().map(|c| &c.token).collect();
+    let mut comments: Vec<ExplainedComment> = Vec::new();
+    for i in 0..arr_data.nrows() {
+        let user = arr_data[[i, 0]].as_text().unwrap_or("");
+        if user != user1 && user != user2 {
+            continue;
+        }
+        let content = arr_data[[i, 1]].as_text().unwrap_or("");
+        let mut in_comment: Vec<String> = crate::text_functions::tokenize(content).into_iter()
+            .filter(|t| shared_set.contains(t))
+            .collect::<HashSet<String>>().into_iter().collect();
+        if in_comment.is_empty() {
+            continue;
+        }
+        in_comment.sort();
+        comments.push(ExplainedComment {
+            row: i,
+            comment_id: crate::duplicate_functions::row_comment_id(arr_data, i),
+            user: user.to_string(),
+            content: content.to_string(),
+            shared_tokens: in_comment,
+        });
+    }
+
+    Ok(PairExplanation {
+        user1: user1.to_string(),
+        user2: user2.to_string(),
+        similarity: crate::find_similarities(user1.to_string(), user2.to_string(), map).unwrap_or(0.0),
+        weighted_similarity: shared_tokens.iter().map(|c| c.contribution).sum(),
+        only_user1: only(words1, words2),
+        only_user2: only(words2, words1),
+        shared_tokens,
+        comments,
+    })
+}
+
+
+impl PairExplanation {
+
+    // purpose: print the explanation for a moderator
+    // input: the explanation
+    // output: none, prints to the terminal
+    pub fn print(&self) {
+        println!("{:?} and {:?}: similarity {:.3} (IDF-weighted {:.3})", self.user1, self.user2, self.similarity, self.weighted_similarity);
+        println!("Shared tokens (idf, share of the weighted similarity):");
+        for c in self.shared_tokens.iter() {
+            println!("  {:<20} {:.3}  {:.3}", c.token, c.idf, c.contribution);
+        }
+        println!("Only {:?}: {:?}", self.user1, self.only_user1);
+        println!("Only {:?}: {:?}", self.user2, self.only_user2);
+        println!("Comments with shared tokens:");
+        for c in self.comments.iter() {
+            println!("  [{}] {:?}: {:?} (shares {:?})", c.comment_id, c.user, c.content, c.shared_tokens);
+        }
+    }
+}
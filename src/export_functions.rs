@@ -0,0 +1,397 @@
+use ndarray::Array2;
+use csv::{ReaderBuilder, WriterBuilder};
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+
+// Module summary: functions that write the similarity graph from create_graph() to files that Gephi, Cytoscape and
+// Graphviz can open (GraphML, GEXF and DOT). Nodes carry their spam label, comment count, videos, community id and language,
+// and edges carry their similarity index as the weight.
+// It also reads and writes the graph as a plain weighted edge list and as a dense or sparse ndarray adjacency matrix,
+// so an expensive create_graph() result can be cached on disk and handed to linear algebra routines.
+
+
+// an undirected edge: (user1, user2, similarity index)
+pub type WeightedEdge = (String, String, f64);
+
+// a graph mapping users to their neighbors, together with its weighted edges
+pub type WeightedGraph = (HashMap<String, Vec<String>>, Vec<WeightedEdge>);
+
+
+// the file formats we can export to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphFormat {
+    GraphML,
+    Gexf,
+    Dot,
+}
+
+
+// It holds everything we know about one user that is worth showing next to their node
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeAttributes {
+    pub is_spammer: bool,
+    pub comment_count: u32,
+    pub videos: Vec<String>, // sorted, without repeats
+    pub community: u32,
+    pub language: String, // the language the user writes in most, from col 6 ("und" if unknown)
+}
+
+
+// purpose: collect the attributes of every node of the graph
+// input: the array of data (cols 0-user, 2-video name), the graph, and the vector of spammers from find_spam
+// output: a hashmap mapping each user of the graph to their NodeAttributes
+// counts comments and collects videos per user in one pass over the rows, takes community ids from find_communities()
+// and languages from language_functions::user_languages()
+pub fn node_attributes(arr_data: &Array2<crate::ColumnVal>, graph: &HashMap<String, Vec<String>>, spammers: &[String]) -> HashMap<String, NodeAttributes> {
+    let mut comment_counts: HashMap<&str, u32> = HashMap::new();
+    let mut videos: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for i in 0..arr_data.nrows() {
+        let user = arr_data[[i, 0]].as_text().unwrap_or("");
+        *comment_counts.entry(user).or_insert(0) += 1;
+        videos.entry(user).or_default().insert(arr_data[[i, 2]].as_text().unwrap_or(""));
+    }
+
+    let spammer_set: HashSet<&String> = spammers.iter().collect();
+    let communities = crate::find_communities(graph);
+    let languages = crate::language_functions::user_languages(arr_data);
+
+    let mut attributes: HashMap<String, NodeAttributes> = HashMap::new();
+    for user in graph.keys() {
+        let mut user_videos: Vec<String> = videos.get(user.as_str())
+            .map(|set| set.iter().map(|v| v.to_string()).collect())
+            .unwrap_or_default();
+        user_videos.sort();
+        attributes.insert(user.clone(), NodeAttributes {
+            is_spammer: spammer_set.contains(user),
+            comment_count: comment_counts.get(user.as_str()).copied().unwrap_or(0),
+            videos: user_videos,
+            community: communities[user],
+            language: languages.get(user).cloned().unwrap_or_else(|| crate::language_functions::UNDETERMINED.to_string()),
+        });
+    }
+    attributes
+}
+
+
+// purpose: list every edge of the graph once, with its weight
+// input: the graph and the hashmap mapping users to the words they used (the one the graph was built from)
+// output: a vector of (user1, user2, similarity index) with user1 < user2, sorted
+pub fn weighted_edges(graph: &HashMap<String, Vec<String>>, map: &HashMap<String, HashSet<String>>) -> Vec<WeightedEdge> {
+    let mut edges: Vec<WeightedEdge> = Vec::new();
+    for (user, neighbors) in graph.iter() {
+        for neighbor in neighbors.iter() {
+            if user < neighbor {
+                let weight = crate::find_similarities(user.clone(), neighbor.clone(), map).unwrap_or(0.0);
+                edges.push((user.clone(), neighbor.clone(), weight));
+            }
+        }
+    }
+    edges.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+    edges
+}
+
+
+// purpose: make a string safe to put inside XML text or a quoted XML attribute
+// input: any string
+// output: the string with &, <, >, " and ' escaped, and with the control characters XML 1.0 forbids removed
+fn escape_xml(text: &str) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if c.is_control() && (c as u32) < 0x20 => (),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+
+// purpose: make a string safe to put inside a double-quoted DOT id
+// input: any string
+// output: the string with backslashes and double quotes escaped and newlines replaced by spaces
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace(['\n', '\r'], " ")
+}
+
+
+// sorted node names, so every export lists nodes in the same order
+fn sorted_nodes(graph: &HashMap<String, Vec<String>>) -> Vec<&String> {
+    let mut nodes: Vec<&String> = graph.keys().collect();
+    nodes.sort();
+    nodes
+}
+
+
+// purpose: write the graph as GraphML
+// input: the graph, the node attributes from node_attributes(), and the edges from weighted_edges()
+// output: the GraphML document as a String
+// nodes get ids n0, n1, ... (user names are kept in the "name" attribute since they can contain anything), and the
+// video list is joined with " | "
+pub fn to_graphml(graph: &HashMap<String, Vec<String>>, attributes: &HashMap<String, NodeAttributes>, edges: &[WeightedEdge]) -> String {
+    let nodes = sorted_nodes(graph);
+    let ids: HashMap<&String, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    out.push_str("  <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n");
+    out.push_str("  <key id=\"spammer\" for=\"node\" attr.name=\"spammer\" attr.type=\"boolean\"/>\n");
+    out.push_str("  <key id=\"comments\" for=\"node\" attr.name=\"comments\" attr.type=\"int\"/>\n");
+    out.push_str("  <key id=\"videos\" for=\"node\" attr.name=\"videos\" attr.type=\"string\"/>\n");
+    out.push_str("  <key id=\"community\" for=\"node\" attr.name=\"community\" attr.type=\"int\"/>\n");
+    out.push_str("  <key id=\"language\" for=\"node\" attr.name=\"language\" attr.type=\"string\"/>\n");
+    out.push_str("  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n");
+    out.push_str("  <graph id=\"similarity\" edgedefault=\"undirected\">\n");
+    for (i, node) in nodes.iter().enumerate() {
+        out.push_str(&format!("    <node id=\"n{}\">\n", i));
+        out.push_str(&format!("      <data key=\"name\">{}</data>\n", escape_xml(node)));
+        if let Some(attr) = attributes.get(*node) {
+            out.push_str(&format!("      <data key=\"spammer\">{}</data>\n", attr.is_spammer));
+            out.push_str(&format!("      <data key=\"comments\">{}</data>\n", attr.comment_count));
+            out.push_str(&format!("      <data key=\"videos\">{}</data>\n", escape_xml(&attr.videos.join(" | "))));
+            out.push_str(&format!("      <data key=\"community\">{}</data>\n", attr.community));
+            out.push_str(&format!("      <data key=\"language\">{}</data>\n", escape_xml(&attr.language)));
+        }
+        out.push_str("    </node>\n");
+    }
+    for (i, (user1, user2, weight)) in edges.iter().enumerate() {
+        out.push_str(&format!("    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\">\n", i, ids[user1], ids[user2]));
+        out.push_str(&format!("      <data key=\"weight\">{}</data>\n", weight));
+        out.push_str("    </edge>\n");
+    }
+    out.push_str("  </graph>\n");
+    out.push_str("</graphml>\n");
+    out
+}
+
+
+// purpose: write the graph as GEXF (Gephi's own format)
+// input: the graph, the node attributes from node_attributes(), and the edges from weighted_edges()
+// output: the GEXF document as a String
+pub fn to_gexf(graph: &HashMap<String, Vec<String>>, attributes: &HashMap<String, NodeAttributes>, edges: &[WeightedEdge]) -> String {
+    let nodes = sorted_nodes(graph);
+    let ids: HashMap<&String, usize> = nodes.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+    out.push_str("  <graph mode=\"static\" defaultedgetype=\"undirected\">\n");
+    out.push_str("    <attributes class=\"node\">\n");
+    out.push_str("      <attribute id=\"0\" title=\"spammer\" type=\"boolean\"/>\n");
+    out.push_str("      <attribute id=\"1\" title=\"comments\" type=\"integer\"/>\n");
+    out.push_str("      <attribute id=\"2\" title=\"videos\" type=\"string\"/>\n");
+    out.push_str("      <attribute id=\"3\" title=\"community\" type=\"integer\"/>\n");
+    out.push_str("      <attribute id=\"4\" title=\"language\" type=\"string\"/>\n");
+    out.push_str("    </attributes>\n");
+    out.push_str("    <nodes>\n");
+    for (i, node) in nodes.iter().enumerate() {
+        out.push_str(&format!("      <node id=\"{}\" label=\"{}\">\n", i, escape_xml(node)));
+        if let Some(attr) = attributes.get(*node) {
+            out.push_str("        <attvalues>\n");
+            out.push_str(&format!("          <attvalue for=\"0\" value=\"{}\"/>\n", attr.is_spammer));
+            out.push_str(&format!("          <attvalue for=\"1\" value=\"{}\"/>\n", attr.comment_count));
+            out.push_str(&format!("          <attvalue for=\"2\" value=\"{}\"/>\n", escape_xml(&attr.videos.join(" | "))));
+            out.push_str(&format!("          <attvalue for=\"3\" value=\"{}\"/>\n", attr.community));
+            out.push_str(&format!("          <attvalue for=\"4\" value=\"{}\"/>\n", escape_xml(&attr.language)));
+            out.push_str("        </attvalues>\n");
+        }
+        out.push_str("      </node>\n");
+    }
+    out.push_str("    </nodes>\n");
+    out.push_str("    <edges>\n");
+    for (i, (user1, user2, weight)) in edges.iter().enumerate() {
+        out.push_str(&format!("      <edge id=\"{}\" source=\"{}\" target=\"{}\" weight=\"{}\"/>\n", i, ids[user1], ids[user2], weight));
+    }
+    out.push_str("    </edges>\n");
+    out.push_str("  </graph>\n");
+    out.push_str("</gexf>\n");
+    out
+}
+
+
+// purpose: write the graph as Graphviz DOT
+// input: the graph, the node attributes from node_attributes(), and the edges from weighted_edges()
+// output: the DOT document as a String
+// spammers are drawn red and everyone else gray; the other attributes are kept as extra node attributes
+pub fn to_dot(graph: &HashMap<String, Vec<String>>, attributes: &HashMap<String, NodeAttributes>, edges: &[WeightedEdge]) -> String {
+    let mut out = String::new();
+    out.push_str("graph similarity {\n");
+    for node in sorted_nodes(graph) {
+        match attributes.get(node) {
+            Some(attr) => out.push_str(&format!("  \"{}\" [spammer={}, comments={}, videos=\"{}\", community={}, language=\"{}\", color={}];\n",
+                escape_dot(node), attr.is_spammer, attr.comment_count, escape_dot(&attr.videos.join(" | ")), attr.community, escape_dot(&attr.language),
+                if attr.is_spammer { "red" } else { "gray" })),
+            None => out.push_str(&format!("  \"{}\";\n", escape_dot(node))),
+        }
+    }
+    for (user1, user2, weight) in edges.iter() {
+        out.push_str(&format!("  \"{}\" -- \"{}\" [weight={}];\n", escape_dot(user1), escape_dot(user2), weight));
+    }
+    out.push_str("}\n");
+    out
+}
+
+
+// purpose: export the graph to a file
+// input: the path to write, the format, the graph, the hashmap of users to words the graph was built from, and the node attributes
+// output: an io Result
+pub fn export_graph(path: &str, format: GraphFormat, graph: &HashMap<String, Vec<String>>, map: &HashMap<String, HashSet<String>>, attributes: &HashMap<String, NodeAttributes>) -> std::io::Result<()> {
+    let edges = weighted_edges(graph, map);
+    let text = match format {
+        GraphFormat::GraphML => to_graphml(graph, attributes, &edges),
+        GraphFormat::Gexf => to_gexf(graph, attributes, &edges),
+        GraphFormat::Dot => to_dot(graph, attributes, &edges),
+    };
+    fs::write(path, text)
+}
+
+
+// purpose: write the graph as a weighted edge list (CSV, TSV, ...)
+// input: the path to write, the delimiter, the graph, and the edges from weighted_edges()
+// output: an io Result
+// writes a "source, target, weight" header, then one line per edge. Users with no neighbors get a line with an empty
+// target and weight, so reading the file back gives the same set of nodes. The csv writer quotes names when needed
+pub fn write_edge_list(path: &str, delimiter: u8, graph: &HashMap<String, Vec<String>>, edges: &[WeightedEdge]) -> std::io::Result<()> {
+    let mut wtr = WriterBuilder::new().delimiter(delimiter).from_path(path)?;
+    wtr.write_record(["source", "target", "weight"])?;
+    for (user1, user2, weight) in edges.iter() {
+        wtr.write_record([user1.as_str(), user2.as_str(), weight.to_string().as_str()])?;
+    }
+    for node in sorted_nodes(graph) {
+        if graph[node].is_empty() {
+            wtr.write_record([node.as_str(), "", ""])?;
+        }
+    }
+    wtr.flush()
+}
+
+
+// purpose: read a graph written by write_edge_list()
+// input: the path of the edge list and its delimiter
+// output: Ok((a graph mapping users to their neighbors, the edges with their weights)) or Err(what went wrong)
+// adds both ends of every edge to each other's neighbor vectors; a line with an empty target only adds the node
+pub fn read_edge_list(path: &str, delimiter: u8) -> Result<WeightedGraph, String> {
+    let mut rdr = ReaderBuilder::new().delimiter(delimiter).has_headers(true).from_path(path)
+        .map_err(|e| format!("could not open {}: {}", path, e))?;
+
+    let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+    let mut edges: Vec<WeightedEdge> = Vec::new();
+    for (num, result) in rdr.records().enumerate() {
+        let record = result.map_err(|e| format!("error reading {}: {}", path, e))?;
+        if record.len() != 3 {
+            return Err(format!("line {} of {} does not have 3 fields", num + 2, path));
+        }
+        let (user1, user2) = (record[0].to_string(), record[1].to_string());
+        graph.entry(user1.clone()).or_default();
+        if user2.is_empty() {
+            continue;
+        }
+        let weight: f64 = record[2].parse().map_err(|_| format!("bad weight on line {} of {}", num + 2, path))?;
+        graph.get_mut(&user1).unwrap().push(user2.clone());
+        graph.entry(user2.clone()).or_default().push(user1.clone());
+        edges.push((user1, user2, weight));
+    }
+    Ok((graph, edges))
+}
+
+
+// purpose: build the dense weighted adjacency matrix of the graph
+// input: the graph and the edges from weighted_edges()
+// output: Ok((the node names in row/column order, a symmetric Array2 with the edge weights and 0 where there is no edge)),
+// or Err if an edge has weight 0 (a graph built with threshold 0.0), since the matrix couldn't tell that edge from no
+// edge and graph_from_adjacency() would drop it. Use the edge list or sparse_adjacency() for such graphs
+pub fn adjacency_matrix(graph: &HashMap<String, Vec<String>>, edges: &[WeightedEdge]) -> Result<(Vec<String>, Array2<f64>), String> {
+    if let Some((user1, user2, _)) = edges.iter().find(|(_, _, weight)| *weight == 0.0) {
+        return Err(format!("the edge {} - {} has weight 0, which a dense matrix can't store", user1, user2));
+    }
+    let nodes: Vec<String> = sorted_nodes(graph).into_iter().cloned().collect();
+    let mut matrix = Array2::<f64>::zeros((nodes.len(), nodes.len()));
+    for (i, j, weight) in sparse_adjacency(graph, edges).1 {
+        matrix[[i, j]] = weight;
+    }
+    Ok((nodes, matrix))
+}
+
+
+// purpose: build the sparse (coordinate list) weighted adjacency matrix of the graph
+// input: the graph and the edges from weighted_edges()
+// output: (the node names in row/column order, a vector of (row, col, weight) entries). Both (i, j) and (j, i) are listed
+pub fn sparse_adjacency(graph: &HashMap<String, Vec<String>>, edges: &[WeightedEdge]) -> (Vec<String>, Vec<(usize, usize, f64)>) {
+    let nodes: Vec<String> = sorted_nodes(graph).into_iter().cloned().collect();
+    let ids: HashMap<&String, usize> = nodes.iter().enumerate().map(|(i, n)| (n, i)).collect();
+    let mut entries: Vec<(usize, usize, f64)> = Vec::new();
+    for (user1, user2, weight) in edges.iter() {
+        if let (Some(i), Some(j)) = (ids.get(user1), ids.get(user2)) {
+            entries.push((*i, *j, *weight));
+            entries.push((*j, *i, *weight));
+        }
+    }
+    entries.sort_by_key(|e| (e.0, e.1));
+    (nodes, entries)
+}
+
+
+// purpose: turn an adjacency matrix back into a graph
+// input: the node names in row/column order and a square adjacency matrix
+// output: (a graph mapping users to their neighbors, the edges with their weights). Any nonzero entry above the diagonal is an edge
+pub fn graph_from_adjacency(nodes: &[String], matrix: &Array2<f64>) -> WeightedGraph {
+    let mut graph: HashMap<String, Vec<String>> = nodes.iter().map(|n| (n.clone(), Vec::new())).collect();
+    let mut edges: Vec<WeightedEdge> = Vec::new();
+    for i in 0..nodes.len() {
+        for j in (i + 1)..nodes.len() {
+            if matrix[[i, j]] != 0.0 {
+                graph.get_mut(&nodes[i]).unwrap().push(nodes[j].clone());
+                graph.get_mut(&nodes[j]).unwrap().push(nodes[i].clone());
+                edges.push((nodes[i].clone(), nodes[j].clone(), matrix[[i, j]]));
+            }
+        }
+    }
+    (graph, edges)
+}
+
+
+// purpose: write a dense adjacency matrix to a CSV file
+// input: the path to write, the node names in row/column order, and the matrix
+// output: an io Result
+// the header row holds the node names; every following row holds one row of the matrix
+pub fn write_adjacency_matrix(path: &str, nodes: &[String], matrix: &Array2<f64>) -> std::io::Result<()> {
+    let mut wtr = WriterBuilder::new().from_path(path)?;
+    wtr.write_record(nodes)?;
+    for row in matrix.rows() {
+        wtr.write_record(row.iter().map(|w| w.to_string()))?;
+    }
+    wtr.flush()
+}
+
+
+// purpose: read a matrix written by write_adjacency_matrix()
+// input: the path of the CSV file
+// output: Ok((the node names, the square matrix)) or Err(what went wrong)
+pub fn read_adjacency_matrix(path: &str) -> Result<(Vec<String>, Array2<f64>), String> {
+    let mut rdr = ReaderBuilder::new().has_headers(true).from_path(path)
+        .map_err(|e| format!("could not open {}: {}", path, e))?;
+    let nodes: Vec<String> = rdr.headers().map_err(|e| format!("error reading {}: {}", path, e))?
+        .iter().map(|n| n.to_string()).collect();
+
+    let mut values: Vec<f64> = Vec::new();
+    let mut num_rows = 0;
+    for result in rdr.records() {
+        let record = result.map_err(|e| format!("error reading {}: {}", path, e))?;
+        for item in record.iter() {
+            values.push(item.parse().map_err(|_| format!("bad weight {:?} in {}", item, path))?);
+        }
+        num_rows += 1;
+    }
+    if num_rows != nodes.len() {
+        return Err(format!("{} has {} rows but {} node names", path, num_rows, nodes.len()));
+    }
+    let matrix = Array2::from_shape_vec((num_rows, nodes.len()), values).map_err(|e| format!("bad matrix in {}: {}", path, e))?;
+    Ok((nodes, matrix))
+}
@@ -0,0 +1,174 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::fs;
+
+// Module summary: author identity resolution. Users were keyed by the raw AUTHOR string, so "Julius NM" and
+// "julius nm " were different nodes. Here spellings are grouped by a normalized key (case and whitespace), an alias
+// file can merge names explicitly, and edit distance suggests other merges for a person to review. The resolved
+// names are written back into col 0, so every map, graph and count after it uses the canonical identity.
+
+
+// how author names are compared
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IdentityOptions {
+    pub fold_case: bool, // "Julius NM" and "julius nm" are the same author
+    pub collapse_whitespace: bool, // leading, trailing and repeated whitespace is ignored
+}
+
+
+// purpose: build the key two spellings of the same author share
+// input: an author name and how to compare names
+// output: the key, e.g. "julius nm" for " Julius   NM" with both options on
+pub fn identity_key(name: &str, options: IdentityOptions) -> String {
+    let spaced = if options.collapse_whitespace { name.split_whitespace().collect::<Vec<&str>>().join(" ") } else { name.to_string() };
+    if options.fold_case { spaced.to_lowercase() } else { spaced }
+}
+
+
+// purpose: read an alias file
+// input: the path of a text file with one "alias<TAB>canonical name" pair per line. Blank lines and lines starting with # are skipped
+// output: Ok(a hashmap from alias to canonical name) or Err(the path and line that could not be read)
+pub fn load_aliases(path: &str) -> Result<HashMap<String, String>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+    let mut aliases: HashMap<String, String> = HashMap::new();
+    for (num, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('\t') {
+            Some((alias, canonical)) if !alias.trim().is_empty() && !canonical.trim().is_empty() => {
+                aliases.insert(alias.to_string(), canonical.trim().to_string());
+            },
+            _ => return Err(format!("{} line {} is not \"alias<TAB>canonical name\"", path, num + 1)),
+        }
+    }
+    Ok(aliases)
+}
+
+
+// purpose: decide the canonical identity of every author
+// input: the array of data (col 0 holds the author), how to compare names, and the aliases from load_aliases()
+// output: a hashmap from every raw author string in the data to its canonical name
+// spellings with the same identity_key() are one author. If an alias (compared by key too) matches any of them, or
+// the group's key is the key of an alias's canonical name, the group takes that canonical name; otherwise it takes its
+// first spelling in the data, with whitespace collapsed when that option is on. Aliases pointing at a name that is
+// itself an alias are followed to the end
+pub fn resolve_identities(arr_data: &Array2<crate::ColumnVal>, options: IdentityOptions, aliases: &HashMap<String, String>) -> HashMap<String, String> {
+    let alias_keys: HashMap<String, &String> = aliases.iter().map(|(alias, canonical)| (identity_key(alias, options), canonical)).collect();
+    let follow = |name: &str| -> Option<String> {
+        let mut current = alias_keys.get(&identity_key(name, options))?.to_string();
+        for _ in 0..alias_keys.len() { // bounded, so an alias loop can't hang
+            match alias_keys.get(&identity_key(&current, options)) {
+                Some(next) if **next != current => current = next.to_string(),
+                _ => break,
+            }
+        }
+        Some(current)
+    };
+
+    // every canonical name is the identity of its own key, so "julius nm" joins an alias to "Julius NM" even when it comes first
+    let mut canonical_of_key: HashMap<String, String> = HashMap::new();
+    let mut targets: Vec<&String> = aliases.values().collect();
+    targets.sort(); // so of two targets with the same key, the same one always wins
+    for target in targets {
+        let canonical = follow(target).unwrap_or_else(|| target.clone());
+        canonical_of_key.entry(identity_key(target, options)).or_insert(canonical);
+    }
+    let mut resolved: HashMap<String, String> = HashMap::new();
+    for i in 0..arr_data.nrows() {
+        let raw = arr_data[[i, 0]].as_text().unwrap_or("");
+        if resolved.contains_key(raw) {
+            continue;
+        }
+        let key = identity_key(raw, options);
+        let canonical = canonical_of_key.entry(key).or_insert_with(|| {
+            follow(raw).unwrap_or_else(|| if options.collapse_whitespace { raw.split_whitespace().collect::<Vec<&str>>().join(" ") } else { raw.to_string() })
+        }).clone();
+        resolved.insert(raw.to_string(), canonical);
+    }
+    resolved
+}
+
+
+// purpose: find the canonical identity of a name typed by a person, e.g. on the command line
+// input: the name, how to compare names, the aliases from load_aliases(), and the resolution from resolve_identities()
+// output: the canonical name of the author the name is a spelling (compared by key) or an alias of, or the name itself
+// when it matches no author
+pub fn resolve_name(name: &str, options: IdentityOptions, aliases: &HashMap<String, String>, resolved: &HashMap<String, String>) -> String {
+    if let Some(canonical) = resolved.get(name) {
+        return canonical.clone();
+    }
+    let mut canonical_of_key: HashMap<String, &String> = resolved.iter().map(|(raw, canonical)| (identity_key(raw, options), canonical)).collect();
+    for canonical in resolved.values() {
+        canonical_of_key.entry(identity_key(canonical, options)).or_insert(canonical);
+    }
+    let key = identity_key(name, options);
+    if let Some(canonical) = canonical_of_key.get(&key) {
+        return canonical.to_string();
+    }
+    match aliases.iter().find(|(alias, _)| identity_key(alias, options) == key) {
+        Some((_, target)) => canonical_of_key.get(&identity_key(target, options)).map_or_else(|| target.clone(), |canonical| canonical.to_string()),
+        None => name.to_string(),
+    }
+}
+
+
+// purpose: write the canonical identities into the data
+// input: the array of data and the resolution from resolve_identities()
+// output: a copy of the array whose col 0 holds canonical names (authors missing from the resolution are kept as they are)
+pub fn canonicalize_authors(arr_data: &Array2<crate::ColumnVal>, resolved: &HashMap<String, String>) -> Array2<crate::ColumnVal> {
+    let mut out = arr_data.clone();
+    for i in 0..out.nrows() {
+        if let Some(canonical) = out[[i, 0]].as_text().and_then(|raw| resolved.get(raw)) {
+            out[[i, 0]] = crate::ColumnVal::One(canonical.clone());
+        }
+    }
+    out
+}
+
+
+// purpose: count the edits (insertions, deletions, substitutions) that turn one string into another
+// input: two strings
+// output: their Levenshtein distance, counted in characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut current = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let substitution = previous[j - 1] + if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            current[j] = substitution.min(previous[j] + 1).min(current[j - 1] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+
+// purpose: suggest authors that may be the same person
+// input: the canonical author names, how to compare names, the max edit distance, and the min length (in characters)
+// of the shorter key, since short names are a few edits away from many others
+// output: a vector of (name1, name2, distance) with name1 < name2, closest first (ties by names). These are only
+// suggestions: nothing is merged until they are added to the alias file
+pub fn merge_suggestions(names: &[String], options: IdentityOptions, max_distance: usize, min_len: usize) -> Vec<(String, String, usize)> {
+    let mut keyed: Vec<(String, &String)> = names.iter().map(|name| (identity_key(name, options), name)).collect();
+    keyed.sort();
+    let lengths: Vec<usize> = keyed.iter().map(|(key, _)| key.chars().count()).collect();
+
+    let mut suggestions: Vec<(String, String, usize)> = Vec::new();
+    for i in 0..keyed.len() {
+        for j in (i + 1)..keyed.len() {
+            if lengths[i].min(lengths[j]) < min_len || lengths[i].abs_diff(lengths[j]) > max_distance {
+                continue;
+            }
+            let distance = edit_distance(&keyed[i].0, &keyed[j].0);
+            if distance <= max_distance {
+                let (a, b) = if keyed[i].1 < keyed[j].1 { (keyed[i].1, keyed[j].1) } else { (keyed[j].1, keyed[i].1) };
+                suggestions.push((a.clone(), b.clone(), distance));
+            }
+        }
+    }
+    suggestions.sort_by(|a, b| a.2.cmp(&b.2).then_with(|| (&a.0, &a.1).cmp(&(&b.0, &b.1))));
+    suggestions
+}
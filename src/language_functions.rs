@@ -0,0 +1,348 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+// Module summary: lightweight script and language identification per comment, with no external service. The script
+// comes from the Unicode blocks of the letters. Latin-script comments are then told apart by character trigram
+// profiles (Cavnar & Trenkle, 1994) built from the sample texts below, with links left out, and are left undetermined
+// when no profile is clearly the closest. The language code is stored as col 6 of the data, so it can be used to
+// filter rows, as a graph node attribute, and to keep users of different languages from being linked.
+
+// the column with_language_column() writes the language code to
+pub const LANGUAGE_COL: usize = 6;
+
+// the code given to comments with too few letters to tell, or in a script we don't know
+pub const UNDETERMINED: &str = "und";
+
+// Latin-script comments with fewer letters than this are too short for trigram profiles to be trusted
+const MIN_LATIN_LETTERS: usize = 8;
+
+// how many of the most frequent trigrams make up a profile
+const PROFILE_SIZE: usize = 300;
+
+// how much farther (as a fraction of the best distance) the second-closest language profile must be for the closest
+// one to be trusted
+const LANGUAGE_MARGIN: f64 = 0.1;
+
+// a few paragraphs of everyday (comment-like) text in each Latin-script language we tell apart. Each is long enough
+// (1,500+ characters) for its PROFILE_SIZE most frequent trigrams to be the language's common ones, not the sample's words
+const LATIN_SAMPLES: [(&str, &str); 6] = [
+    ("en", "I love this song so much, it is the best video on youtube and I listen to it every day. Please check out my channel \
+            and subscribe if you like my music. Who is still watching this in the year? This is one of the most amazing songs \
+            that I have ever heard and the beat is great. Thank you for sharing, you are the best and we will never forget that \
+            feeling when the music started. My friends and I used to play this at every party when we were young, and now my \
+            kids are dancing to it in the kitchen. Nobody can tell me that music was better back then, because this is still \
+            the greatest thing that ever happened. If you are reading this comment, I hope you have a wonderful day and that \
+            all of your dreams come true. I made a new video about how to make money online without paying anything, so go \
+            and watch it before they take it down. Share this with your family and your friends, they will thank you later. \
+            The singer has such a beautiful voice and the dancers in the video are really good. How many people watched this \
+            more than ten times? Give this a thumbs up so everyone can see it. I am a small artist trying to get my songs out \
+            there, and it would mean the world to me if you could listen to one of them and tell me what you think about it. \
+            Every time I hear this I remember the summer when we went to the beach with our whole family and sang it together \
+            in the car. It is crazy how a song can bring back so many memories. What was your favorite part of the video? \
+            Mine was the end, when everybody started dancing in the street and the whole city joined them."),
+    ("es", "Me encanta esta canción, es la mejor del mundo y la escucho todos los días. Por favor visiten mi canal y suscríbanse \
+            si les gusta mi música. Quién sigue viendo este video en este año? Es una de las canciones más bonitas que he \
+            escuchado en mi vida. Gracias por compartir, eres el mejor y nunca vamos a olvidar lo que sentimos cuando empezó. \
+            Mis amigos y yo la poníamos en todas las fiestas cuando éramos jóvenes, y ahora mis hijos la bailan en la cocina. \
+            Nadie me puede decir que la música de antes era mejor, porque esta sigue siendo lo más grande que ha pasado. Si \
+            estás leyendo este comentario, espero que tengas un día maravilloso y que todos tus sueños se hagan realidad. Hice \
+            un video nuevo sobre cómo ganar dinero por internet sin pagar nada, así que vayan a verlo antes de que lo quiten. \
+            Compartan esto con su familia y con sus amigos, después les van a dar las gracias. El cantante tiene una voz muy \
+            hermosa y los bailarines del video son muy buenos. Cuántas personas vieron esto más de diez veces? Denle me gusta \
+            para que todos lo puedan ver. Soy un artista pequeño que intenta dar a conocer sus canciones, y para mí sería muy \
+            importante que escucharan una de ellas y me dijeran qué piensan. Cada vez que la escucho me acuerdo del verano en \
+            que fuimos a la playa con toda la familia y la cantamos juntos en el coche. Es increíble cómo una canción puede \
+            traer tantos recuerdos. Cuál fue tu parte favorita del video? La mía fue el final, cuando todos empezaron a bailar \
+            en la calle y toda la ciudad se unió a ellos."),
+    ("pt", "Eu amo essa música, é a melhor do mundo e eu escuto todos os dias. Por favor visitem o meu canal e se inscrevam se \
+            vocês gostam da minha música. Quem ainda está assistindo esse vídeo neste ano? É uma das músicas mais lindas que eu \
+            já ouvi na minha vida. Obrigado por compartilhar, você é o melhor e nunca vamos esquecer o que sentimos quando começou. \
+            Meus amigos e eu colocávamos essa música em todas as festas quando éramos jovens, e agora os meus filhos dançam ela \
+            na cozinha. Ninguém pode me dizer que a música de antigamente era melhor, porque essa continua sendo a coisa mais \
+            incrível que já aconteceu. Se você está lendo este comentário, espero que tenha um dia maravilhoso e que todos os \
+            seus sonhos se realizem. Eu fiz um vídeo novo sobre como ganhar dinheiro na internet sem pagar nada, então vão lá \
+            ver antes que tirem do ar. Compartilhem isso com a sua família e com os seus amigos, depois eles vão agradecer. O \
+            cantor tem uma voz muito bonita e os dançarinos do vídeo são muito bons. Quantas pessoas viram isso mais de dez \
+            vezes? Deixem o seu like para que todo mundo possa ver. Eu sou um artista pequeno tentando mostrar as minhas \
+            músicas, e seria muito importante para mim se vocês ouvissem uma delas e me dissessem o que acharam. Toda vez que \
+            eu escuto essa música eu lembro do verão em que fomos para a praia com a família inteira e cantamos juntos no \
+            carro. É incrível como uma música pode trazer tantas lembranças. Qual foi a sua parte favorita do vídeo? A minha \
+            foi o final, quando todo mundo começou a dançar na rua e a cidade inteira se juntou a eles."),
+    ("fr", "J'adore cette chanson, c'est la meilleure du monde et je l'écoute tous les jours. S'il vous plaît allez voir ma \
+            chaîne et abonnez-vous si vous aimez ma musique. Qui regarde encore cette vidéo cette année? C'est une des plus \
+            belles chansons que j'ai entendue dans ma vie. Merci pour le partage, tu es le meilleur et on n'oubliera jamais. \
+            Mes amis et moi la mettions à toutes les fêtes quand nous étions jeunes, et maintenant mes enfants dansent dessus \
+            dans la cuisine. Personne ne peut me dire que la musique d'avant était meilleure, parce que celle-ci reste la plus \
+            belle chose qui soit arrivée. Si tu lis ce commentaire, je te souhaite une très bonne journée et que tous tes rêves \
+            se réalisent. J'ai fait une nouvelle vidéo sur la façon de gagner de l'argent sur internet sans rien payer, alors \
+            allez la regarder avant qu'ils la suppriment. Partagez ceci avec votre famille et vos amis, ils vous remercieront \
+            plus tard. Le chanteur a une très belle voix et les danseurs de la vidéo sont vraiment bons. Combien de personnes \
+            ont regardé ça plus de dix fois? Mettez un pouce bleu pour que tout le monde puisse le voir. Je suis un petit \
+            artiste qui essaie de faire connaître ses chansons, et ce serait très important pour moi si vous pouviez en \
+            écouter une et me dire ce que vous en pensez. Chaque fois que je l'entends, je me souviens de l'été où nous sommes \
+            allés à la plage avec toute la famille et où nous l'avons chantée ensemble dans la voiture. C'est fou comme une \
+            chanson peut ramener autant de souvenirs. Quel était ton moment préféré de la vidéo? Le mien était la fin, quand \
+            tout le monde a commencé à danser dans la rue et que toute la ville les a rejoints."),
+    ("de", "Ich liebe dieses Lied, es ist das beste der Welt und ich höre es jeden Tag. Bitte schaut euch meinen Kanal an und \
+            abonniert ihn, wenn euch meine Musik gefällt. Wer schaut sich dieses Video noch in diesem Jahr an? Das ist eines der \
+            schönsten Lieder, die ich je in meinem Leben gehört habe. Danke fürs Teilen, du bist der Beste und wir vergessen es nie. \
+            Meine Freunde und ich haben es auf jeder Party gespielt, als wir jung waren, und jetzt tanzen meine Kinder in der \
+            Küche dazu. Niemand kann mir erzählen, dass die Musik früher besser war, denn das hier ist immer noch das Größte, \
+            was je passiert ist. Wenn du diesen Kommentar liest, wünsche ich dir einen wunderschönen Tag und dass alle deine \
+            Träume wahr werden. Ich habe ein neues Video darüber gemacht, wie man im Internet Geld verdient, ohne etwas zu \
+            bezahlen, also schaut es euch an, bevor sie es löschen. Teilt das mit eurer Familie und euren Freunden, sie werden \
+            euch später dankbar sein. Der Sänger hat eine so schöne Stimme und die Tänzer im Video sind wirklich gut. Wie viele \
+            Leute haben sich das mehr als zehnmal angesehen? Gebt dem einen Daumen nach oben, damit es alle sehen können. Ich \
+            bin ein kleiner Künstler, der versucht, seine Lieder bekannt zu machen, und es würde mir sehr viel bedeuten, wenn \
+            ihr euch eines davon anhört und mir sagt, was ihr darüber denkt. Jedes Mal, wenn ich es höre, erinnere ich mich an \
+            den Sommer, als wir mit der ganzen Familie an den Strand gefahren sind und es zusammen im Auto gesungen haben. Es \
+            ist verrückt, wie ein Lied so viele Erinnerungen zurückbringen kann. Was war dein Lieblingsteil des Videos? Meiner \
+            war das Ende, als alle auf der Straße anfingen zu tanzen und sich die ganze Stadt ihnen anschloss."),
+    ("it", "Adoro questa canzone, è la migliore del mondo e la ascolto tutti i giorni. Per favore visitate il mio canale e \
+            iscrivetevi se vi piace la mia musica. Chi sta ancora guardando questo video quest'anno? È una delle canzoni più \
+            belle che abbia mai sentito nella mia vita. Grazie per la condivisione, sei il migliore e non dimenticheremo mai. \
+            Io e i miei amici la mettevamo a tutte le feste quando eravamo giovani, e adesso i miei figli la ballano in cucina. \
+            Nessuno mi può dire che la musica di una volta era migliore, perché questa è ancora la cosa più bella che sia mai \
+            successa. Se stai leggendo questo commento, ti auguro una giornata meravigliosa e che tutti i tuoi sogni si \
+            avverino. Ho fatto un nuovo video su come guadagnare soldi su internet senza pagare niente, quindi andate a \
+            guardarlo prima che lo tolgano. Condividete questo con la vostra famiglia e con i vostri amici, vi ringrazieranno \
+            più tardi. Il cantante ha una voce bellissima e i ballerini del video sono davvero bravi. Quante persone hanno \
+            guardato questo più di dieci volte? Mettete un mi piace così tutti lo possono vedere. Sono un piccolo artista che \
+            cerca di far conoscere le sue canzoni, e per me sarebbe molto importante se ne ascoltaste una e mi diceste cosa ne \
+            pensate. Ogni volta che la sento mi ricordo dell'estate in cui siamo andati al mare con tutta la famiglia e \
+            l'abbiamo cantata insieme in macchina. È incredibile come una canzone possa riportare così tanti ricordi. Qual è \
+            stata la tua parte preferita del video? La mia è stata la fine, quando tutti hanno cominciato a ballare per strada \
+            e tutta la città si è unita a loro."),
+];
+
+
+// the writing systems we recognize
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Script {
+    Latin,
+    Cyrillic,
+    Greek,
+    Arabic,
+    Hebrew,
+    Devanagari,
+    Thai,
+    Han, // Chinese characters, also used in Japanese
+    Kana, // Japanese hiragana and katakana
+    Hangul,
+    Other, // letters of any other script
+}
+
+
+// purpose: find the script of a character
+// input: a character
+// output: Some(its script) for letters, None for digits, punctuation, emoji and the like
+pub fn script_of(c: char) -> Option<Script> {
+    if !c.is_alphabetic() {
+        return None;
+    }
+    let script = match c as u32 {
+        0x0041..=0x024F | 0x1E00..=0x1EFF | 0xFF21..=0xFF5A => Script::Latin, // basic, Latin-1, extended, and full-width letters
+        0x0370..=0x03FF | 0x1F00..=0x1FFF => Script::Greek,
+        0x0400..=0x052F => Script::Cyrillic,
+        0x0590..=0x05FF => Script::Hebrew,
+        0x0600..=0x06FF | 0x0750..=0x077F => Script::Arabic,
+        0x0900..=0x097F => Script::Devanagari,
+        0x0E00..=0x0E7F => Script::Thai,
+        0x3040..=0x30FF => Script::Kana,
+        0x4E00..=0x9FFF | 0x3400..=0x4DBF => Script::Han,
+        0xAC00..=0xD7AF | 0x1100..=0x11FF => Script::Hangul,
+        _ => Script::Other,
+    };
+    Some(script)
+}
+
+
+// purpose: find the script most of a text's letters are written in
+// input: a text
+// output: Some(the script with the most letters, ties broken by the order of the Script enum), or None if there are no letters
+pub fn dominant_script(text: &str) -> Option<Script> {
+    let mut counts: HashMap<Script, usize> = HashMap::new();
+    for script in text.chars().filter_map(script_of) {
+        *counts.entry(script).or_insert(0) += 1;
+    }
+    counts.into_iter().max_by(|a, b| a.1.cmp(&b.1).then_with(|| (b.0 as u8).cmp(&(a.0 as u8)))).map(|(script, _)| script)
+}
+
+
+// purpose: count the character trigrams of a text
+// input: a text
+// output: a hashmap from trigram to count. Words are lowercased letters padded with '_' on both sides, so "hi" gives "_hi" and "hi_"
+fn trigram_counts(text: &str) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for word in text.split(|c: char| !c.is_alphabetic()).filter(|w| !w.is_empty()) {
+        let padded: Vec<char> = format!("_{}_", word.to_lowercase()).chars().collect();
+        for trigram in padded.windows(3) {
+            *counts.entry(trigram.iter().collect()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+
+// purpose: rank the trigrams of a text by frequency
+// input: a text and how many trigrams to keep
+// output: a hashmap from each of the top trigrams to its rank (0 = most frequent, ties by trigram)
+fn trigram_profile(text: &str, size: usize) -> HashMap<String, usize> {
+    let mut ranked: Vec<(String, usize)> = trigram_counts(text).into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked.into_iter().take(size).enumerate().map(|(rank, (trigram, _))| (trigram, rank)).collect()
+}
+
+
+// the profiles of LATIN_SAMPLES, built the first time they are needed
+fn latin_profiles() -> &'static Vec<(&'static str, HashMap<String, usize>)> {
+    static PROFILES: OnceLock<Vec<(&'static str, HashMap<String, usize>)>> = OnceLock::new();
+    PROFILES.get_or_init(|| LATIN_SAMPLES.iter().map(|(code, sample)| (*code, trigram_profile(sample, PROFILE_SIZE))).collect())
+}
+
+
+// purpose: guess the language of a Latin-script text
+// input: a text
+// output: the code of the sample language whose profile is closest by the out-of-place distance: the sum over the
+// text's trigrams of how far its rank is from the rank in the language profile (PROFILE_SIZE if the language lacks it).
+// UNDETERMINED when the runner-up is within LANGUAGE_MARGIN of the best, since short texts often sit about as close
+// to every profile and the best one is then down to a couple of shared trigrams
+fn closest_latin_language(text: &str) -> &'static str {
+    let profile = trigram_profile(text, PROFILE_SIZE);
+    let mut distances: Vec<(&'static str, usize)> = latin_profiles().iter()
+        .map(|(code, language_profile)| {
+            let distance: usize = profile.iter()
+                .map(|(trigram, rank)| language_profile.get(trigram).map_or(PROFILE_SIZE, |other| rank.abs_diff(*other)))
+                .sum();
+            (*code, distance)
+        })
+        .collect();
+    distances.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+    match distances.as_slice() {
+        [(code, best), (_, second), ..] if (second - best) as f64 >= LANGUAGE_MARGIN * *best as f64 => code,
+        _ => UNDETERMINED,
+    }
+}
+
+
+// purpose: remove the URLs and @handles from a comment
+// input: the comment content
+// output: the content without the whitespace-separated words that hold a link. Domains, paths and channel names are
+// not words of the comment's language, and link-only spam would otherwise be profiled from them
+fn strip_links(content: &str) -> String {
+    content.split_whitespace()
+        .filter(|word| crate::link_functions::extract_links(word).is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+
+// purpose: identify the language of a comment
+// input: the comment content
+// output: a language code: "en", "es", "pt", "fr", "de" or "it" for Latin script, "ru" for Cyrillic, "el", "ar", "he",
+// "hi", "th", "ja" (kana, or Han mixed with kana), "zh" (Han only), "ko", or UNDETERMINED when there are no letters,
+// too few Latin letters, or the script is not one of these
+pub fn detect_language(content: &str) -> &'static str {
+    match dominant_script(content) {
+        Some(Script::Latin) => {
+            let text = strip_links(content);
+            if text.chars().filter(|c| script_of(*c) == Some(Script::Latin)).count() < MIN_LATIN_LETTERS {
+                UNDETERMINED
+            } else {
+                closest_latin_language(&text)
+            }
+        },
+        Some(Script::Cyrillic) => "ru",
+        Some(Script::Greek) => "el",
+        Some(Script::Arabic) => "ar",
+        Some(Script::Hebrew) => "he",
+        Some(Script::Devanagari) => "hi",
+        Some(Script::Thai) => "th",
+        Some(Script::Kana) => "ja",
+        Some(Script::Han) => if content.chars().any(|c| script_of(c) == Some(Script::Kana)) { "ja" } else { "zh" },
+        Some(Script::Hangul) => "ko",
+        Some(Script::Other) | None => UNDETERMINED,
+    }
+}
+
+
+// purpose: store the language of every comment on its row
+// input: the array of data
+// output: a copy of the array with the language code of each comment's content in col 6 (LANGUAGE_COL). Arrays
+// without the date and comment id cols are first padded with empty ones, so the language always lands in col 6
+pub fn with_language_column(arr_data: &Array2<crate::ColumnVal>) -> Array2<crate::ColumnVal> {
+    let mut out = arr_data.clone();
+    while out.ncols() < LANGUAGE_COL {
+        let empty = vec![crate::ColumnVal::One(String::new()); out.nrows()];
+        out.push_column(ndarray::ArrayView1::from(&empty)).expect("column has one value per row");
+    }
+    let languages: Vec<crate::ColumnVal> = (0..out.nrows())
+        .map(|i| crate::ColumnVal::One(detect_language(out[[i, 1]].as_text().unwrap_or("")).to_string()))
+        .collect();
+    out.push_column(ndarray::ArrayView1::from(&languages)).expect("column has one value per row");
+    out
+}
+
+
+// purpose: read the language of one row
+// input: the array of data and a row index
+// output: Some(language code), or None when the array has no language column
+pub fn row_language(arr_data: &Array2<crate::ColumnVal>, row: usize) -> Option<&str> {
+    if arr_data.ncols() <= LANGUAGE_COL {
+        return None;
+    }
+    arr_data[[row, LANGUAGE_COL]].as_text()
+}
+
+
+// purpose: keep only the comments in some languages
+// input: the array of data (with the language column) and the language codes to keep
+// output: a new array with only those rows, in the same order
+pub fn filter_languages(arr_data: &Array2<crate::ColumnVal>, languages: &[&str]) -> Array2<crate::ColumnVal> {
+    let rows: Vec<usize> = (0..arr_data.nrows()).filter(|i| row_language(arr_data, *i).is_some_and(|l| languages.contains(&l))).collect();
+    crate::split_functions::select_rows(arr_data, &rows)
+}
+
+
+// purpose: find the language each user writes in most
+// input: the array of data (with the language column)
+// output: a hashmap from user to their most common language code (ties by code). Comments of undetermined language
+// don't count, so a user is only UNDETERMINED when none of their comments could be identified
+pub fn user_languages(arr_data: &Array2<crate::ColumnVal>) -> HashMap<String, String> {
+    let mut counts: HashMap<String, HashMap<&str, usize>> = HashMap::new();
+    for i in 0..arr_data.nrows() {
+        let languages = counts.entry(arr_data[[i, 0]].as_text().unwrap_or("").to_string()).or_default();
+        if let Some(code) = row_language(arr_data, i).filter(|code| *code != UNDETERMINED) {
+            *languages.entry(code).or_insert(0) += 1;
+        }
+    }
+    counts.into_iter().map(|(user, languages)| {
+        let best = languages.into_iter().max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0))).map(|(code, _)| code.to_string());
+        (user, best.unwrap_or_else(|| UNDETERMINED.to_string()))
+    }).collect()
+}
+
+
+// purpose: tell whether two language codes may be compared
+// input: two language codes
+// output: true if they are the same, or either is UNDETERMINED (too short to tell, so it could be either)
+pub fn compatible_languages(a: &str, b: &str) -> bool {
+    a == b || a == UNDETERMINED || b == UNDETERMINED
+}
+
+
+// purpose: keep only the edges between users who write in the same language
+// input: a graph from create_graph() and the users' languages from user_languages()
+// output: a copy of the graph without the edges whose users have different known languages. Token overlap between
+// languages is mostly names, numbers and links, so such edges say little; users of UNDETERMINED language (or missing
+// from the map) keep all their edges
+pub fn same_language_graph(graph: &HashMap<String, Vec<String>>, languages: &HashMap<String, String>) -> HashMap<String, Vec<String>> {
+    let language_of = |user: &String| languages.get(user).map_or(UNDETERMINED, |code| code.as_str());
+    graph.iter()
+        .map(|(user, neighbors)| {
+            let kept: Vec<String> = neighbors.iter().filter(|n| compatible_languages(language_of(user), language_of(n))).cloned().collect();
+            (user.clone(), kept)
+        })
+        .collect()
+}
@@ -0,0 +1,221 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Module summary: finds what a comment promotes: URLs (with or without http://), their domains, YouTube video and
+// channel links, and @handles. map_users_to_words() strips the dots and slashes out of these, so they are read from
+// the raw content instead. The promoted destination is often the strongest sign of a campaign, so there are per-user
+// and per-domain counts and a user <-> domain graph.
+
+// top-level domains a link without http:// or www. may end in. Anything else ("hi.how") is taken to be a typo
+const BARE_TLDS: [&str; 20] = ["com", "net", "org", "info", "biz", "io", "co", "me", "tv", "ly", "be", "gl", "gd", "us", "uk", "de", "br", "ru", "se", "fm"];
+
+
+// what a link points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkKind {
+    Url, // any other web address
+    YouTubeVideo, // youtu.be/<id> or youtube.com/watch?v=<id>
+    YouTubeChannel, // youtube.com/channel/<id>, /user/<name>, /c/<name> or /@<name>
+    Handle, // @name written in the comment
+}
+
+
+// It is one link found in a comment
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub kind: LinkKind,
+    pub text: String, // the link as written, without surrounding punctuation and with &amp; decoded
+    pub domain: Option<String>, // lowercase host without "www.", None for handles
+    pub target: Option<String>, // the video id, the channel path (e.g. "user/name") or the handle's name, if any
+}
+
+
+// It is the links of one user
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserLinkStats {
+    pub user: String,
+    pub links: u32, // every link, handles included
+    pub youtube_links: u32,
+    pub handles: u32,
+    pub domains: Vec<String>, // distinct, sorted
+}
+
+
+// It is how much one domain is promoted
+#[derive(Debug, Clone, PartialEq)]
+pub struct DomainStats {
+    pub domain: String,
+    pub links: u32,
+    pub spam_links: u32, // links in comments labeled spam
+    pub users: Vec<String>, // distinct, sorted
+}
+
+
+// purpose: split a URL into its host and the part after it
+// input: a URL with or without a scheme
+// output: (lowercase host without "www.", the rest starting at the first '/', '?' or '#', or "")
+fn split_host(url: &str) -> (String, &str) {
+    let lower = url.to_lowercase();
+    let start = if lower.starts_with("https://") { 8 } else if lower.starts_with("http://") { 7 } else { 0 };
+    let rest = &url[start..];
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let host = rest[..end].to_lowercase();
+    (host.strip_prefix("www.").unwrap_or(&host).to_string(), &rest[end..])
+}
+
+
+// whether a word without a scheme looks like a domain: 2+ dot-separated labels of letters, digits and '-', the last one a known TLD
+fn is_bare_domain(host: &str) -> bool {
+    let labels: Vec<&str> = host.split('.').collect();
+    labels.len() >= 2
+        && labels.iter().all(|l| !l.is_empty() && l.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'))
+        && BARE_TLDS.contains(&labels[labels.len() - 1])
+}
+
+
+// purpose: read the video id or channel out of a YouTube link
+// input: the host and the part after it
+// output: Some((kind, target)) for a YouTube video or channel link, None otherwise
+fn youtube_target(host: &str, path: &str) -> Option<(LinkKind, String)> {
+    let segments: Vec<&str> = path.trim_start_matches('/').split(['/', '?', '#', '&']).collect();
+    let first = segments.first().copied().unwrap_or("");
+    if host == "youtu.be" && !first.is_empty() {
+        return Some((LinkKind::YouTubeVideo, first.to_string()));
+    }
+    if host != "youtube.com" && host != "m.youtube.com" {
+        return None;
+    }
+    if let Some(id) = path.split(['?', '&']).find_map(|param| param.strip_prefix("v=")) {
+        return Some((LinkKind::YouTubeVideo, id.split('#').next().unwrap_or("").to_string()));
+    }
+    match (first, segments.get(1)) {
+        ("channel" | "user" | "c", Some(name)) if !name.is_empty() => Some((LinkKind::YouTubeChannel, format!("{}/{}", first, name))),
+        (name, _) if name.starts_with('@') && name.len() > 1 => Some((LinkKind::YouTubeChannel, name.to_string())),
+        _ => None,
+    }
+}
+
+
+// purpose: find every link in a comment
+// input: the comment content
+// output: a vector of Links in the order they appear
+// splits on whitespace and on the characters HTML puts around links (<, >, ", '), then trims punctuation off both
+// ends of each word. A word is a URL when it starts with http://, https:// or www., or is a bare domain; a word starting
+// with @ is a handle (e-mail addresses are not, since their @ is in the middle)
+pub fn extract_links(content: &str) -> Vec<Link> {
+    let mut links: Vec<Link> = Vec::new();
+    for word in content.split(|c: char| c.is_whitespace() || "<>\"'\u{feff}".contains(c)) {
+        let word = word.trim_start_matches(['(', '[', '{', ',', ';', ':']).trim_end_matches(['.', ',', '!', '?', ';', ':', ')', ']', '}']);
+        if word.is_empty() {
+            continue;
+        }
+        let text = word.replace("&amp;", "&");
+        let lower = text.to_lowercase();
+
+        if let Some(name) = text.strip_prefix('@') {
+            let name: String = name.chars().take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '.').collect();
+            let name = name.trim_end_matches('.');
+            if !name.is_empty() {
+                links.push(Link { kind: LinkKind::Handle, text: format!("@{}", name), domain: None, target: Some(name.to_lowercase()) });
+            }
+            continue;
+        }
+
+        let (host, path) = split_host(&text);
+        let has_prefix = lower.starts_with("http://") || lower.starts_with("https://") || lower.starts_with("www.");
+        if host.is_empty() || !(has_prefix || is_bare_domain(&host)) {
+            continue;
+        }
+        let (kind, target) = match youtube_target(&host, path) {
+            Some((kind, target)) => (kind, Some(target)),
+            None => (LinkKind::Url, None),
+        };
+        links.push(Link { kind, text, domain: Some(host), target });
+    }
+    links
+}
+
+
+// purpose: find the links of every comment
+// input: the array of data (col 1 holds the content)
+// output: a vector with the links of each row, in row order
+pub fn links_by_row(arr_data: &Array2<crate::ColumnVal>) -> Vec<Vec<Link>> {
+    (0..arr_data.nrows()).map(|i| extract_links(arr_data[[i, 1]].as_text().unwrap_or(""))).collect()
+}
+
+
+// purpose: count the links of each user
+// input: the array of data and its links from links_by_row()
+// output: a vector of UserLinkStats for the users with at least one link, most links first (ties by name)
+pub fn user_link_stats(arr_data: &Array2<crate::ColumnVal>, links: &[Vec<Link>]) -> Vec<UserLinkStats> {
+    let mut by_user: HashMap<String, (UserLinkStats, HashSet<String>)> = HashMap::new();
+    for (i, row_links) in links.iter().enumerate().filter(|(_, l)| !l.is_empty()) {
+        let user = arr_data[[i, 0]].as_text().unwrap_or("").to_string();
+        let (stats, domains) = by_user.entry(user.clone()).or_insert_with(||
+            (UserLinkStats { user, links: 0, youtube_links: 0, handles: 0, domains: Vec::new() }, HashSet::new()));
+        for link in row_links.iter() {
+            stats.links += 1;
+            match link.kind {
+                LinkKind::YouTubeVideo | LinkKind::YouTubeChannel => stats.youtube_links += 1,
+                LinkKind::Handle => stats.handles += 1,
+                LinkKind::Url => (),
+            }
+            if let Some(domain) = &link.domain {
+                domains.insert(domain.clone());
+            }
+        }
+    }
+    let mut out: Vec<UserLinkStats> = by_user.into_values().map(|(mut stats, domains)| {
+        stats.domains = domains.into_iter().collect();
+        stats.domains.sort();
+        stats
+    }).collect();
+    out.sort_by(|a, b| b.links.cmp(&a.links).then_with(|| a.user.cmp(&b.user)));
+    out
+}
+
+
+// purpose: count the links to each domain
+// input: the array of data (cols 0-user and 3-class) and its links from links_by_row()
+// output: a vector of DomainStats, most links first (ties by domain)
+pub fn domain_stats(arr_data: &Array2<crate::ColumnVal>, links: &[Vec<Link>]) -> Vec<DomainStats> {
+    let mut by_domain: HashMap<String, (u32, u32, HashSet<String>)> = HashMap::new();
+    for (i, row_links) in links.iter().enumerate() {
+        for domain in row_links.iter().filter_map(|link| link.domain.as_ref()) {
+            let entry = by_domain.entry(domain.clone()).or_insert((0, 0, HashSet::new()));
+            entry.0 += 1;
+            if arr_data[[i, 3]].as_bool() == Some(true) {
+                entry.1 += 1;
+            }
+            entry.2.insert(arr_data[[i, 0]].as_text().unwrap_or("").to_string());
+        }
+    }
+    let mut out: Vec<DomainStats> = by_domain.into_iter().map(|(domain, (links, spam_links, users))| {
+        let mut users: Vec<String> = users.into_iter().collect();
+        users.sort();
+        DomainStats { domain, links, spam_links, users }
+    }).collect();
+    out.sort_by(|a, b| b.links.cmp(&a.links).then_with(|| a.domain.cmp(&b.domain)));
+    out
+}
+
+
+// purpose: build the user <-> domain graph
+// input: the domain stats from domain_stats()
+// output: a graph in the same shape as create_graph() returns, linking each user to every domain they linked to and
+// back. Domain nodes are named "domain:<domain>" so they can't clash with user names; only users with links are in it
+pub fn user_domain_graph(domains: &[DomainStats]) -> HashMap<String, Vec<String>> {
+    let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+    for stats in domains.iter() {
+        let node = format!("domain:{}", stats.domain);
+        for user in stats.users.iter() {
+            graph.entry(user.clone()).or_default().push(node.clone());
+        }
+        graph.insert(node, stats.users.clone());
+    }
+    for neighbors in graph.values_mut() {
+        neighbors.sort();
+    }
+    graph
+}
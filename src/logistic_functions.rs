@@ -0,0 +1,158 @@
+use ndarray::{Array1, Array2};
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Module summary: a logistic regression spam classifier. Comments are turned into ndarray feature matrices
+// (bag-of-words counts or TF-IDF), and the weights are learned by gradient descent with an L2 penalty.
+// The learned coefficients tell us which tokens push a comment towards spam.
+
+
+// which numbers go into the feature matrix for each (comment, token) cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeatureKind {
+    BagOfWords, // how many times the token appears in the comment
+    TfIdf, // term frequency times inverse document frequency, each row scaled to length 1
+}
+
+
+// It maps tokens to feature columns. It is fit once on the training data and then reused for any new comment
+#[derive(Debug, Clone)]
+pub struct FeatureBuilder {
+    pub kind: FeatureKind,
+    pub vocab: Vec<String>, // vocab[j] is the token of feature column j
+    index: HashMap<String, usize>,
+    idf: Array1<f64>,
+}
+
+impl FeatureBuilder {
+
+    // purpose: build the vocabulary (and idf weights) from labeled comments
+    // input: an array with col 1 holding comment content, the kind of features, and the min # of comments a token must appear in
+    // output: a FeatureBuilder
+    // counts in how many comments each token appears, keeps the ones that appear in at least min_docs comments (sorted so
+    // the column order is always the same), and computes a smoothed idf = ln((1 + n) / (1 + df)) + 1 for each
+    pub fn fit(arr_data: &Array2<crate::ColumnVal>, kind: FeatureKind, min_docs: u32) -> FeatureBuilder {
+        let mut doc_freq: HashMap<String, u32> = HashMap::new();
+        for row in arr_data.rows() {
+            let unique_tokens: HashSet<String> = crate::text_functions::tokenize(row[1].as_text().unwrap_or("")).into_iter().collect();
+            for token in unique_tokens {
+                *doc_freq.entry(token).or_insert(0) += 1;
+            }
+        }
+
+        let mut vocab: Vec<String> = doc_freq.iter()
+            .filter(|(_, df)| **df >= min_docs)
+            .map(|(token, _)| token.clone())
+            .collect();
+        vocab.sort();
+
+        let num_docs = arr_data.nrows() as f64;
+        let mut index: HashMap<String, usize> = HashMap::new();
+        let mut idf = Array1::<f64>::zeros(vocab.len());
+        for (j, token) in vocab.iter().enumerate() {
+            index.insert(token.clone(), j);
+            idf[j] = ((1.0 + num_docs) / (1.0 + doc_freq[token] as f64)).ln() + 1.0;
+        }
+
+        FeatureBuilder { kind, vocab, index, idf }
+    }
+
+    // purpose: turn one comment into a feature vector
+    // input: the comment content
+    // output: an Array1 with one entry per vocabulary token. Tokens outside the vocabulary are ignored
+    pub fn transform_text(&self, content: &str) -> Array1<f64> {
+        let mut features = Array1::<f64>::zeros(self.vocab.len());
+        for token in crate::text_functions::tokenize(content) {
+            if let Some(j) = self.index.get(&token) {
+                features[*j] += 1.0;
+            }
+        }
+        if self.kind == FeatureKind::TfIdf {
+            features *= &self.idf;
+            let norm = features.dot(&features).sqrt();
+            if norm > 0.0 {
+                features /= norm;
+            }
+        }
+        features
+    }
+
+    // purpose: turn every comment of an array into a feature matrix
+    // input: an array with col 1 holding comment content
+    // output: an Array2 with one row per comment and one column per vocabulary token
+    pub fn transform(&self, arr_data: &Array2<crate::ColumnVal>) -> Array2<f64> {
+        let mut matrix = Array2::<f64>::zeros((arr_data.nrows(), self.vocab.len()));
+        for (i, row) in arr_data.rows().into_iter().enumerate() {
+            matrix.row_mut(i).assign(&self.transform_text(row[1].as_text().unwrap_or("")));
+        }
+        matrix
+    }
+}
+
+
+// It holds the learned weights (one per vocabulary token) and the bias of the logistic regression
+#[derive(Debug, Clone)]
+pub struct LogisticModel {
+    pub features: FeatureBuilder,
+    pub weights: Array1<f64>,
+    pub bias: f64,
+}
+
+
+// the logistic function, squashing any real number into (0, 1)
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+impl LogisticModel {
+
+    // purpose: train the classifier on labeled comments
+    // input: an array with cols 1-content and 3-class, the feature builder to use, the L2 penalty, the learning rate and the # of epochs
+    // output: a trained LogisticModel
+    // builds the feature matrix X and label vector y, then runs full-batch gradient descent on the mean log loss plus
+    // (l2 / 2) * |w|^2. The bias is not penalized
+    pub fn train(arr_data: &Array2<crate::ColumnVal>, features: FeatureBuilder, l2: f64, learning_rate: f64, epochs: u32) -> LogisticModel {
+        let x: Array2<f64> = features.transform(arr_data);
+        let y: Array1<f64> = arr_data.column(3).iter()
+            .map(|cell| if cell.as_bool().unwrap_or(false) { 1.0 } else { 0.0 })
+            .collect();
+        let num_rows = x.nrows().max(1) as f64;
+
+        let mut weights = Array1::<f64>::zeros(x.ncols());
+        let mut bias = 0.0;
+
+        for _ in 0..epochs {
+            let predictions: Array1<f64> = (x.dot(&weights) + bias).mapv(sigmoid);
+            let errors: Array1<f64> = predictions - &y;
+            let weight_grad: Array1<f64> = x.t().dot(&errors) / num_rows + &weights * l2;
+            let bias_grad = errors.sum() / num_rows;
+            weights = weights - weight_grad * learning_rate;
+            bias -= bias_grad * learning_rate;
+        }
+
+        LogisticModel { features, weights, bias }
+    }
+
+    // purpose: find the probability that a comment is spam
+    // input: the comment content
+    // output: P(spam | content) between 0 and 1
+    pub fn spam_probability(&self, content: &str) -> f64 {
+        sigmoid(self.features.transform_text(content).dot(&self.weights) + self.bias)
+    }
+
+    // purpose: classify a comment
+    // input: the comment content
+    // output: true if the comment is more likely spam than not
+    pub fn predict(&self, content: &str) -> bool {
+        self.spam_probability(content) >= 0.5
+    }
+
+    // purpose: list the learned coefficient of every token
+    // input: the model
+    // output: a vector of (token, coefficient) sorted from most spam-indicative (largest) to most ham-indicative (smallest)
+    pub fn coefficients(&self) -> Vec<(String, f64)> {
+        let mut coefs: Vec<(String, f64)> = self.features.vocab.iter().cloned().zip(self.weights.iter().copied()).collect();
+        coefs.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        coefs
+    }
+}
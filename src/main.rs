@@ -6,6 +6,26 @@ use std::collections::VecDeque;
 
 mod csv_functions;
 mod spam_functions;
+mod text_functions;
+mod bayes_functions;
+mod logistic_functions;
+mod split_functions;
+mod eval_functions;
+mod propagation_functions;
+mod export_functions;
+mod snapshot_functions;
+mod bipartite_functions;
+mod time_functions;
+mod window_functions;
+mod duplicate_functions;
+mod coordination_functions;
+mod explain_functions;
+mod vocab_functions;
+mod link_functions;
+mod language_functions;
+mod normalize_functions;
+mod identity_functions;
+mod audit_functions;
 
 
 // It wraps different types as different variants of the same type since arrays must have elements of the same type
@@ -13,6 +33,43 @@ mod spam_functions;
 pub enum ColumnVal {
     One(String),
     Two(bool),
+    Three(f64), // numbers, e.g. the counts and means made by DataFrame::group_by()
+}
+
+impl ColumnVal {
+    // returns the text inside a One, or None for a bool
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            ColumnVal::One(text) => Some(text),
+            ColumnVal::Two(_) | ColumnVal::Three(_) => None,
+        }
+    }
+
+    // returns the bool inside a Two, or None for text
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ColumnVal::One(_) | ColumnVal::Three(_) => None,
+            ColumnVal::Two(some_bool) => Some(*some_bool),
+        }
+    }
+
+    // returns a Three as is and a Two as 1.0 or 0.0, or None for text
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            ColumnVal::One(_) => None,
+            ColumnVal::Two(some_bool) => Some(if *some_bool { 1.0 } else { 0.0 }),
+            ColumnVal::Three(number) => Some(*number),
+        }
+    }
+
+    // returns any value as a String, e.g. to use it as a hashmap key
+    pub fn to_key(&self) -> String {
+        match self {
+            ColumnVal::One(text) => text.clone(),
+            ColumnVal::Two(some_bool) => some_bool.to_string(),
+            ColumnVal::Three(number) => number.to_string(),
+        }
+    }
 }
 
 
@@ -23,38 +80,352 @@ struct DataFrame {
     data: Array2<ColumnVal>,
     total_users: u32,
     total_spam_users: u32,
+    spammer_policy: spam_functions::SpammerPolicy, // the definition of spammer total_spam_users was counted with
+}
+
+
+// It is one row of the per-video breakdown made by DataFrame::video_stats()
+#[derive(Debug, Clone, PartialEq)]
+struct VideoStats {
+    video: String,
+    total_comments: u32,
+    spam_comments: u32,
+    spam_rate: f64,
+    unique_users: u32,
+    unique_spammers: u32, // users with at least one spam comment on this video
+    top_tokens: Vec<(String, u32)>, // most used tokens in this video's comments, most used first
+}
+
+// what DataFrame::group_by() groups the rows on
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GroupKey {
+    Column(usize), // the value of a column, e.g. 0-user, 2-video name, 3-class
+    DateBucket(time_functions::TimeBucket), // the hour, day or week of the date (col 4). Rows without a date share the key ""
+}
+
+// what DataFrame::group_by() computes for each group. The usize is the column the aggregation reads
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Aggregation {
+    Count, // # of rows in the group
+    CountDistinct(usize), // # of different values in the column
+    SumBool(usize), // # of true values in the column
+    Mean(usize), // mean of the column's numbers (bools count as 1 and 0)
+    ConcatTokens(usize), // every token of the column's text, joined with spaces
 }
 
 fn main() {
 
-    let my_arr: Array2<ColumnVal> = csv_functions::read_CSV_using_reader("Youtube-Spam-Dataset.csv");
-    let (my_map, users) = map_users_to_words(&my_arr); // my_map maps a user to a hashset of words they used. users is a vector of unique users in some random order
-    
-    let (num_spam_users, spammers) = spam_functions::find_spam(&my_arr, &users); // spammers is a vector of unique spammers
-    let df = DataFrame::new(my_arr, users.len() as u32, num_spam_users); // a dataframe for all users in the dataset
+    // `cargo run -- --normalize ...` runs every normalization step on the content before anything tokenizes it, so the
+    // word map, the graphs, the snapshot and the classifiers all see "ch3ck" and "сheck" as "check". It is off by default.
+    // `--same-language` drops the similarity edges between users whose detected languages differ, in every graph below.
+    // It is off by default too, since link-only spam pushed by many accounts often gets told apart as different languages.
+    // `--dedup=<keys>` picks the deduplication passes: a comma-separated list of id and author-content (the default is
+    // both), or none. `--no-fold-case` and `--no-collapse-whitespace` keep author spellings that differ in case or
+    // whitespace apart, and `--aliases=<path>` reads the author aliases from another file than author_aliases.tsv.
+    // The options are taken out of args, so the positional arguments below don't see them
+    let args: Vec<String> = std::env::args().collect();
+    let normalize_content = args.iter().any(|arg| arg == "--normalize");
+    let same_language = args.iter().any(|arg| arg == "--same-language");
+    let identity_options = identity_functions::IdentityOptions {
+        fold_case: !args.iter().any(|arg| arg == "--no-fold-case"),
+        collapse_whitespace: !args.iter().any(|arg| arg == "--no-collapse-whitespace"),
+    };
+    let aliases_path = args.iter().find_map(|arg| arg.strip_prefix("--aliases=")).unwrap_or("author_aliases.tsv").to_string();
+    let dedup_keys = match args.iter().find_map(|arg| arg.strip_prefix("--dedup=")) {
+        Some(text) => match csv_functions::parse_dedup_keys(text) {
+            Ok(keys) => keys,
+            Err(err) => {
+                println!("usage: --dedup=id,author-content|none: {}", err);
+                return;
+            },
+        },
+        None => vec![csv_functions::DedupKey::CommentId, csv_functions::DedupKey::AuthorContent],
+    };
+    let args: Vec<String> = args.into_iter().filter(|arg| !arg.starts_with("--")).collect();
+
+    // each chosen deduplication pass drops repeated rows and reports how many
+    let mut dedup_reports: Vec<csv_functions::DedupReport> = Vec::new();
+    let mut dedup = |arr: Array2<ColumnVal>, key: csv_functions::DedupKey| -> Array2<ColumnVal> {
+        if !dedup_keys.contains(&key) {
+            return arr;
+        }
+        let (deduped, report) = csv_functions::deduplicate(&arr, key).expect("the reader loads the comment id column");
+        dedup_reports.push(report);
+        deduped
+    };
+
+    // rows repeating a COMMENT_ID are dropped (unless --dedup leaves out id), and every comment gets its detected
+    // language, in col 6
+    let raw_arr = dedup(csv_functions::read_CSV_using_reader("Youtube-Spam-Dataset.csv"), csv_functions::DedupKey::CommentId);
+    let raw_arr: Array2<ColumnVal> = language_functions::with_language_column(&raw_arr);
+
+    // Author identity resolution: spellings that differ only in case or whitespace are one author (as far as the options
+    // above allow), and the aliases file (alias<TAB>canonical name per line, if it exists) merges more. Everything below
+    // uses the canonical names
+    let aliases = if std::path::Path::new(&aliases_path).exists() {
+        identity_functions::load_aliases(&aliases_path).unwrap_or_else(|err| {
+            println!("Ignoring the author aliases: {}", err);
+            HashMap::new()
+        })
+    } else {
+        HashMap::new()
+    };
+    let identities = identity_functions::resolve_identities(&raw_arr, identity_options, &aliases);
+    // then the same (canonical) author posting the same text twice only counts once (unless --dedup leaves out
+    // author-content)
+    let deduped_arr = dedup(identity_functions::canonicalize_authors(&raw_arr, &identities), csv_functions::DedupKey::AuthorContent);
+
+    let my_arr: Array2<ColumnVal> = if normalize_content {
+        normalize_functions::normalize_content(&deduped_arr, normalize_functions::NormalizeOptions::all())
+    } else {
+        deduped_arr.clone()
+    };
+
+    // `cargo run -- explain <user1> <user2>` only explains why two users are connected, instead of running the whole analysis
+    if args.get(1).map(|arg| arg.as_str()) == Some("explain") {
+        if args.len() != 4 {
+            println!("usage: explain <user1> <user2>");
+            return;
+        }
+        let (my_map, _) = map_users_to_words(&my_arr);
+        let user1 = identity_functions::resolve_name(&args[2], identity_options, &aliases, &identities);
+        let user2 = identity_functions::resolve_name(&args[3], identity_options, &aliases, &identities);
+        match explain_functions::explain_pair(&my_arr, &my_map, &user1, &user2) {
+            Ok(explanation) => explanation.print(),
+            Err(err) => println!("Could not explain the pair: {}", err),
+        }
+        return;
+    }
+
+    // The word map, users, spam labels and 0.7 similarity graph come from analysis_snapshot.bin when it was built from the
+    // same rows (compared by hash, so changed comments, labels or aliases are noticed) and the same threshold. Otherwise
+    // they are rebuilt (the slow part) and the snapshot is rewritten
+    let snapshot = match snapshot_functions::Snapshot::load("analysis_snapshot.bin") {
+        Ok(snap) if snap.matches(&my_arr, 0.7) => {
+            println!("Loaded analysis_snapshot.bin");
+            Some(snap)
+        },
+        Ok(_) => {
+            println!("analysis_snapshot.bin was built from other data or another threshold, rebuilding it");
+            None
+        },
+        Err(err) => {
+            println!("Not using a snapshot ({}), rebuilding it", err);
+            None
+        },
+    };
+    let snapshot = snapshot.unwrap_or_else(|| {
+        let (my_map, users) = map_users_to_words(&my_arr); // my_map maps a user to a hashset of words they used. users is a vector of unique users in some random order
+        let (_, spammers) = spam_functions::find_spam(&my_arr, &users); // spammers is a vector of unique spammers
+        let my_graph_sim: HashMap<String, Vec<String>> = create_graph(&users, &my_map, 0.7); // maps a name to a vector of names of people whose similarity index with them is at least the threshold
+        let snap = snapshot_functions::Snapshot { source_rows: my_arr.nrows() as u64, data_hash: csv_functions::data_hash(&my_arr), threshold: 0.7, users, user_words: my_map, graph: my_graph_sim, spammers };
+        if let Err(err) = snap.save("analysis_snapshot.bin") {
+            println!("Could not save analysis_snapshot.bin: {}", err);
+        }
+        snap
+    });
+    let snapshot_functions::Snapshot { user_words: my_map, users, graph: my_graph_sim, spammers, .. } = snapshot;
+
+    // with --same-language, users writing in different languages are not compared: their edges are dropped from every
+    // similarity graph below
+    let user_languages = same_language.then(|| language_functions::user_languages(&my_arr));
+    let language_filter = |graph: HashMap<String, Vec<String>>| match &user_languages {
+        Some(languages) => language_functions::same_language_graph(&graph, languages),
+        None => graph,
+    };
+    let count_edges = |graph: &HashMap<String, Vec<String>>| graph.values().map(|neighbors| neighbors.len()).sum::<usize>() / 2;
+    let edges_before = count_edges(&my_graph_sim);
+    let my_graph_sim = language_filter(my_graph_sim);
+    if same_language {
+        println!("Dropped {} of {} similarity edges between users writing in different languages", edges_before - count_edges(&my_graph_sim), edges_before);
+    }
 
-    let my_graph_sim: HashMap<String, Vec<String>> = create_graph(&users, &my_map, 0.7); // maps a name to a vector of names of people whose similarity index with them is at least the threshold
+    // who counts as a spammer. The snapshot holds the AnySpam spammers; the spammers, the spam-only subset and the quick
+    // stats below all follow this policy instead
+    let spammer_policy = spam_functions::SpammerPolicy::AnySpam;
+    let any_spam_users = spammers.len();
+    let (num_spam_users, spammers) = spam_functions::find_spam_with_policy(&my_arr, spammer_policy);
+    let mut df = DataFrame::new(my_arr, users.len() as u32, num_spam_users); // a dataframe for all users in the dataset
+    df.set_spammer_policy(spammer_policy);
+
+    for report in dedup_reports.iter() {
+        println!("Deduplication by {:?}: kept {} rows, dropped {}", report.key, report.kept, report.dropped);
+    }
+    println!("{} author spellings resolved to {} authors", identities.len(), users.len());
+    let suggestions = identity_functions::merge_suggestions(&users, identity_options, 1, 6);
+    if !suggestions.is_empty() {
+        println!("  Names 1 edit apart ({} found); add a line to {} to merge a pair:", suggestions.len(), aliases_path);
+    }
+    for (name1, name2, _) in suggestions.iter().take(5) {
+        println!("  {}\t{}", name2, name1);
+    }
 
     let num_graphs_all_nodes = find_num_disconnected_graphs(&my_graph_sim);
     println!("Num graphs (all nodes included), threshold 0.7: {:?}", num_graphs_all_nodes);
     
     
 
-    // Spam only
-    let spam_arr: Array2<ColumnVal> = csv_functions::spam_specific_arr("Youtube-Spam-Dataset.csv");
+    // Spam only: the spam comments of the policy's spammers, taken from the same (deduplicated, canonicalized) rows as df
+    let spam_arr: Array2<ColumnVal> = spam_functions::spam_subset(&df.data, &spammers);
+    println!("Spam-only subset ({:?}): {} comments of the {} spam rows in the CSV", spammer_policy, spam_arr.nrows(),
+        csv_functions::spam_specific_arr("Youtube-Spam-Dataset.csv").nrows());
     let (map_of_spam, spammers2) = map_users_to_words(&spam_arr); // spammers2 is the same vector as spammers; the order might be a little different
 
-    let my_graph_spam_only: HashMap<String, Vec<String>> = create_graph(&spammers2, &map_of_spam, 0.7);
+    let my_graph_spam_only: HashMap<String, Vec<String>> = language_filter(create_graph(&spammers2, &map_of_spam, 0.7));
     let num_disconnected_graphs_spam_only = find_num_disconnected_graphs(&my_graph_spam_only);
     println!("Num graphs in the spam-only network, threshold 0.7: {:?}", num_disconnected_graphs_spam_only);
 
     // Some quick stats
     df.quick_stats();
+    println!("Spammers under each definition ({} users have at least one spam comment):", any_spam_users);
+    for policy in [spam_functions::SpammerPolicy::AnySpam, spam_functions::SpammerPolicy::MajoritySpam, spam_functions::SpammerPolicy::AllSpam,
+                   spam_functions::SpammerPolicy::RatioAtLeast(0.25), spam_functions::SpammerPolicy::AtLeastK(2)] {
+        let (count, _) = spam_functions::find_spam_with_policy(&df.data, policy);
+        println!("  {:?}: {}", policy, count);
+    }
+    let mut summaries = spam_functions::spam_summary(&df.data);
+    summaries.sort_by(|a, b| b.spam_comments.cmp(&a.spam_comments).then_with(|| a.first_row.cmp(&b.first_row)));
+    println!("Users with the most spam comments:");
+    for summary in summaries.iter().take(5) {
+        let seen = |timestamp: Option<i64>| timestamp.map_or("no date".to_string(), time_functions::format_timestamp);
+        println!("  {:?}: {} of {} comments spam (rows {}-{}), first seen {}, last seen {}", summary.user, summary.spam_comments, summary.comments,
+            summary.first_row, summary.last_row, seen(summary.first_seen), seen(summary.last_seen));
+    }
+
+    // ... and the same stats per video, most spam-heavy first
+    let mut per_video = df.video_stats(5);
+    per_video.sort_by(|a, b| b.spam_rate.total_cmp(&a.spam_rate));
+    println!("Per-video stats (sorted by spam rate):");
+    for row in per_video.iter() {
+        println!("  {:?}: {} comments, {} spam ({:.1}%), {} users, {} spammers, top tokens {:?}",
+            row.video, row.total_comments, row.spam_comments, 100.0 * row.spam_rate, row.unique_users, row.unique_spammers, row.top_tokens);
+    }
+    if let Err(err) = write_video_stats("video_stats.csv", &per_video) {
+        println!("Could not write video_stats.csv: {}", err);
+    }
+
+    // Ad-hoc questions with group_by: per video, per label and per language, then the most active authors
+    let aggregations = [Aggregation::Count, Aggregation::CountDistinct(0), Aggregation::SumBool(3), Aggregation::Mean(3), Aggregation::ConcatTokens(1)];
+    for (name, key) in [("video", GroupKey::Column(2)), ("label", GroupKey::Column(3)), ("language", GroupKey::Column(language_functions::LANGUAGE_COL))] {
+        let grouped = df.group_by(key, &aggregations);
+        println!("Grouped by {} (comments, distinct authors, spam comments, spam rate, # of tokens):", name);
+        for row in grouped.data.rows() {
+            let num_tokens = row[5].as_text().unwrap_or("").split(' ').filter(|t| !t.is_empty()).count();
+            println!("  {}: {} {} {} {:.3} {}", row[0].to_key(), row[1].to_key(), row[2].to_key(), row[3].to_key(), row[4].as_number().unwrap_or(f64::NAN), num_tokens);
+        }
+    }
+    let non_english = language_functions::filter_languages(&df.data, &["es", "pt", "fr", "de", "it", "ru", "el", "ar", "he", "hi", "th", "ja", "zh", "ko"]);
+    println!("{} comments are in a language other than English, {} of them spam", non_english.nrows(),
+        non_english.column(3).iter().filter(|cell| cell.as_bool() == Some(true)).count());
+    let by_author = df.group_by(GroupKey::Column(0), &[Aggregation::Count, Aggregation::SumBool(3)]);
+    let mut most_active: Vec<_> = by_author.data.rows().into_iter().collect();
+    most_active.sort_by(|a, b| b[1].as_number().unwrap_or(0.0).total_cmp(&a[1].as_number().unwrap_or(0.0)).then_with(|| a[0].to_key().cmp(&b[0].to_key())));
+    println!("Most active authors (comments, spam comments): {:?}",
+        most_active.iter().take(5).map(|row| (row[0].to_key(), row[1].to_key(), row[2].to_key())).collect::<Vec<_>>());
+    println!();
+
+    // Temporal analysis: the busiest days, the weekly spam rate per video, and bursts of similar comments
+    let undated = (0..df.data.nrows()).filter(|i| time_functions::row_timestamp(&df.data, *i).is_none()).count();
+    let mut daily = time_functions::time_series(&df.data, time_functions::TimeBucket::Daily, None);
+    daily.sort_by(|a, b| b.comments.cmp(&a.comments).then_with(|| a.start.cmp(&b.start)));
+    println!("Busiest days ({} comments have no date):", undated);
+    for day in daily.iter().take(5) {
+        println!("  {}: {} comments, spam rate {:.3}", day.label, day.comments, day.spam_rate);
+    }
+    for (video, weekly) in time_functions::time_series_by_video(&df.data, time_functions::TimeBucket::Weekly) {
+        let peak = weekly.iter().max_by_key(|week| week.spam_comments);
+        if let Some(peak) = peak {
+            println!("  {:?}: active in {} weeks, most spam in the week of {} ({} of {} comments)", video, weekly.len(), peak.label, peak.spam_comments, peak.comments);
+        }
+    }
+    let by_hour = df.group_by(GroupKey::DateBucket(time_functions::TimeBucket::Hourly), &[Aggregation::Count, Aggregation::Mean(3)]);
+    println!("Comments fall into {} distinct hours", by_hour.data.nrows());
+    let user_bursts = time_functions::detect_bursts(&df.data, None, 3_600, 3, 0.3);
+    let communities = find_communities(&my_graph_sim);
+    let cluster_bursts = time_functions::detect_bursts(&df.data, Some(&communities), 3_600, 3, 0.3);
+    println!("Bursts (3+ similar comments within an hour, more than usual for the poster): {} by single users, {} by similarity-graph clusters of 2+ users", user_bursts.len(), cluster_bursts.len());
+    for (prefix, bursts) in [("user ", &user_bursts), ("", &cluster_bursts)] { // cluster keys already read "cluster <id>"
+        for burst in bursts.iter().take(3) {
+            println!("  {}{}: {} comments from {} to {} (similarity {:.2})", prefix, burst.key, burst.rows.len(),
+                time_functions::format_timestamp(burst.start), time_functions::format_timestamp(burst.end), burst.mean_similarity);
+        }
+    }
+    println!();
+
+    // Time-windowed similarity graphs: 7-day windows moved 1 day at a time, and how their communities evolve
+    let windows = window_functions::build_window_graphs(&df.data, 7, 1, 0.7, user_languages.as_ref());
+    let events = window_functions::community_events(&windows);
+    println!("Built {} 7-day similarity graphs", windows.len());
+    for kind in [window_functions::EventKind::Birth, window_functions::EventKind::Death, window_functions::EventKind::Merge,
+                 window_functions::EventKind::Split, window_functions::EventKind::Continue] {
+        println!("  {:?} events: {}", kind, events.iter().filter(|event| event.kind == kind).count());
+    }
+    if let Some(busiest) = windows.iter().max_by_key(|w| (w.communities.first().map_or(0, |c| c.len()), -w.start)) {
+        println!("Biggest windowed community: {} users from {} to {} ({} users in that window's graph), top spammers {:?}",
+            busiest.communities.first().map_or(0, |c| c.len()), time_functions::format_timestamp(busiest.start),
+            time_functions::format_timestamp(busiest.end), busiest.graph.len(), busiest.top_spammers);
+    }
+    for event in events.iter().filter(|event| event.kind == window_functions::EventKind::Merge).take(3) {
+        println!("  merge in the week from {}: {:?}", time_functions::format_timestamp(windows[event.window].start), windows[event.window].communities[event.after[0]]);
+    }
+    println!();
+
+    // Comment-level near-duplicates: copy-paste campaigns are clusters of near-identical comments from several authors
+    let duplicate_clusters = duplicate_functions::find_near_duplicates(&df.data, 5, 20, 4, 0.8);
+    let campaigns: Vec<&duplicate_functions::DuplicateCluster> = duplicate_clusters.iter().filter(|cluster| cluster.authors.len() >= 2).collect();
+    println!("Near-duplicate comments: {} clusters covering {} comments, {} of them posted by 2+ authors",
+        duplicate_clusters.len(), duplicate_clusters.iter().map(|cluster| cluster.rows.len()).sum::<usize>(), campaigns.len());
+    for cluster in campaigns.iter().take(5) {
+        println!("  {} comments by {} authors on {} videos, e.g. {:?}: {:?}", cluster.rows.len(), cluster.authors.len(), cluster.videos.len(),
+            cluster.comment_ids[0], df.data[[cluster.rows[0], 1]].as_text().unwrap_or("").chars().take(60).collect::<String>());
+    }
+    if let Err(err) = duplicate_functions::write_duplicate_clusters("near_duplicates.csv", &df.data, &duplicate_clusters) {
+        println!("Could not write near_duplicates.csv: {}", err);
+    }
+    println!();
+
+    // Promoted destinations: URLs, domains, YouTube links and @handles, per user and per domain
+    let links = link_functions::links_by_row(&df.data);
+    let link_users = link_functions::user_link_stats(&df.data, &links);
+    let domains = link_functions::domain_stats(&df.data, &links);
+    let domain_graph = link_functions::user_domain_graph(&domains);
+    let count_kind = |kind: link_functions::LinkKind| links.iter().flatten().filter(|link| link.kind == kind).count();
+    println!("Links: {} URLs, {} YouTube videos, {} YouTube channels, {} @handles, posted by {} users to {} domains ({} nodes in the user-domain graph)",
+        count_kind(link_functions::LinkKind::Url), count_kind(link_functions::LinkKind::YouTubeVideo), count_kind(link_functions::LinkKind::YouTubeChannel),
+        count_kind(link_functions::LinkKind::Handle), link_users.len(), domains.len(), domain_graph.len());
+    for stats in domains.iter().take(5) {
+        println!("  {}: {} links ({} in spam) by {} users", stats.domain, stats.links, stats.spam_links, stats.users.len());
+    }
+    for stats in link_users.iter().take(3) {
+        println!("  {:?}: {} links ({} YouTube, {} handles) to {:?}", stats.user, stats.links, stats.youtube_links, stats.handles, stats.domains);
+    }
+    println!();
+
+    // Label audit: annotations that disagree with each other, written out for the annotators to re-check
+    let audit = audit_functions::audit_labels(&df.data, 0.8);
+    println!("Label audit: {} authors with mixed labels, {} texts labeled both ways, {} near-duplicate clusters labeled both ways",
+        audit.mixed_authors.len(), audit.identical_texts.len(), audit.near_duplicates.len());
+    for issue in audit.identical_texts.iter().chain(audit.near_duplicates.iter()).take(3) {
+        println!("  {:?} {:?}: {} spam, {} ham", issue.kind, issue.key.chars().take(60).collect::<String>(), issue.spam_rows.len(), issue.ham_rows.len());
+    }
+    if let Err(err) = audit.write_report("label_audit.csv", &df.data) {
+        println!("Could not write label_audit.csv: {}", err);
+    }
+    println!();
+
+    // Suspected sockpuppets: pairs and groups of users with similar vocabularies, the same videos and posts minutes apart
+    let coordination = coordination_functions::coordination_report(&df.data, &my_graph_sim, &my_map, 10, 5, 0.5);
+    coordination.print(5);
+    if let Some(top) = coordination.pairs.first() {
+        println!();
+        explain_functions::explain_pair(&df.data, &my_map, &top.user1, &top.user2).expect("users of the report are in the map").print();
+    }
+    println!();
 
     // Analyzing number of disconnected graphs based on different similarity thresholds
     let thresholds = vec![0.0, 0.2, 0.5, 0.9, 1.0];
     for threshold in thresholds.iter() {
-        let spam_graph: HashMap<String, Vec<String>> = create_graph(&spammers2, &map_of_spam, *threshold);
+        let spam_graph: HashMap<String, Vec<String>> = language_filter(create_graph(&spammers2, &map_of_spam, *threshold));
         let num_disconnected = find_num_disconnected_graphs(&spam_graph);
         println!("Num graphs in the spam-only network, threshold {:?}: {:?}", threshold, num_disconnected);
     }
@@ -74,6 +445,160 @@ fn main() {
     println!("");
     println!("There was/were {:?} best spammer(s) (who had the most similarities with other spammers). They were {:?}, and used the following words: {:?}", num_best, best_spammers, best_words);
 
+    // Optional normalization before tokenization: how many comments each step changes, and how much the vocabulary shrinks
+    // (counted on the content as read, whether or not --normalize applied the steps to the analysis above)
+    let count_vocab = |arr: &Array2<ColumnVal>| (0..arr.nrows()).flat_map(|i| text_functions::tokenize(arr[[i, 1]].as_text().unwrap_or(""))).collect::<HashSet<String>>().len();
+    let normalize_steps = [
+        ("NFKC", normalize_functions::NormalizeOptions { nfkc: true, ..Default::default() }),
+        ("confusable folding", normalize_functions::NormalizeOptions { fold_confusables: true, ..Default::default() }),
+        ("diacritic stripping", normalize_functions::NormalizeOptions { strip_diacritics: true, ..Default::default() }),
+        ("leetspeak mapping", normalize_functions::NormalizeOptions { map_leetspeak: true, ..Default::default() }),
+        ("all steps", normalize_functions::NormalizeOptions::all()),
+    ];
+    println!("{} distinct tokens before normalization ({})", count_vocab(&deduped_arr),
+        if normalize_content { "the analysis used all steps" } else { "pass --normalize to analyze the normalized content" });
+    for (name, options) in normalize_steps {
+        let normalized = normalize_functions::normalize_content(&deduped_arr, options);
+        let changed = (0..deduped_arr.nrows()).filter(|i| deduped_arr[[*i, 1]] != normalized[[*i, 1]]).count();
+        println!("  {}: changes {} comments, {} distinct tokens", name, changed, count_vocab(&normalized));
+    }
+
+    // best_words mixes in filler every user writes. Ranking tokens against the ham comments keeps only the telling ones
+    let vocab = vocab_functions::score_vocabulary(&df.data, 5);
+    for method in [vocab_functions::VocabScore::LogOdds, vocab_functions::VocabScore::ChiSquare, vocab_functions::VocabScore::Pmi] {
+        for spam in [true, false] {
+            let top: Vec<String> = vocab.top_tokens(method, spam, 10).iter()
+                .map(|score| format!("{} ({}/{})", score.token, score.spam_count, score.ham_count))
+                .collect();
+            println!("Top {} tokens by {:?} (spam/ham count): {}", if spam { "spam" } else { "ham" }, method, top.join(", "));
+        }
+    }
+
+    // Naive Bayes classifier trained on every labeled comment
+    // (reuses the saved model file if it was trained on these same rows with the same alpha, otherwise trains and saves
+    // a new one)
+    let nb_alpha = 1.0;
+    let nb_model = match bayes_functions::NaiveBayesModel::load("naive_bayes_model.txt") {
+        Ok(model) if model.trained_on(&df.data, nb_alpha) => model,
+        _ => {
+            let model = bayes_functions::NaiveBayesModel::train(&df.data, nb_alpha);
+            if let Err(err) = model.save("naive_bayes_model.txt") {
+                println!("Could not save the Naive Bayes model: {}", err);
+            }
+            model
+        }
+    };
+    println!();
+    let sample_comments = ["Check out my channel and subscribe!", "This song is amazing, I love it"];
+    for comment in sample_comments.iter() {
+        println!("Naive Bayes: {:?} has spam probability {:.3} (spam: {:?})", comment, nb_model.spam_probability(comment), nb_model.predict(comment));
+    }
+
+    // Logistic regression on bag-of-words and on TF-IDF features. Its coefficients show which tokens push a comment towards spam or ham
+    for kind in [logistic_functions::FeatureKind::BagOfWords, logistic_functions::FeatureKind::TfIdf] {
+        let features = logistic_functions::FeatureBuilder::fit(&df.data, kind, 2);
+        let lr_model = logistic_functions::LogisticModel::train(&df.data, features, 0.001, 0.5, 200);
+        println!();
+        for comment in sample_comments.iter() {
+            println!("Logistic regression ({:?}): {:?} has spam probability {:.3} (spam: {:?})", kind, comment, lr_model.spam_probability(comment), lr_model.predict(comment));
+        }
+        let coefs = lr_model.coefficients();
+        println!("Most spam-indicative tokens ({:?}): {:?}", kind, &coefs[..10.min(coefs.len())]);
+        println!("Most ham-indicative tokens ({:?}): {:?}", kind, coefs.iter().rev().take(10).collect::<Vec<_>>());
+    }
+
+    // Held-out evaluation: one seeded 80/20 split by author, then 5-fold cross-validation of Naive Bayes under each grouping
+    let nb_accuracy = |train: &Array2<ColumnVal>, test: &Array2<ColumnVal>| -> f64 {
+        let model = bayes_functions::NaiveBayesModel::train(train, 1.0);
+        let report = eval_functions::evaluate_scorer(test, |row| model.spam_probability(row[1].as_text().unwrap_or("")), 0.5, 10);
+        report.confusion.accuracy()
+    };
+    println!();
+    let (train_arr, test_arr) = split_functions::train_test_split(&df.data, 0.2, 210, split_functions::GroupBy::Author);
+
+    let held_out_nb = bayes_functions::NaiveBayesModel::train(&train_arr, 1.0);
+    eval_functions::evaluate_scorer(&test_arr, |row| held_out_nb.spam_probability(row[1].as_text().unwrap_or("")), 0.5, 10).print("Naive Bayes (held-out authors)");
+
+    let held_out_features = logistic_functions::FeatureBuilder::fit(&train_arr, logistic_functions::FeatureKind::TfIdf, 2);
+    let held_out_lr = logistic_functions::LogisticModel::train(&train_arr, held_out_features, 0.001, 0.5, 200);
+    eval_functions::evaluate_scorer(&test_arr, |row| held_out_lr.spam_probability(row[1].as_text().unwrap_or("")), 0.5, 10).print("logistic regression (held-out authors)");
+
+    // a graph heuristic scored the same way: the author's degree in the similarity graph, relative to the max degree
+    let max_degree = my_graph_sim.values().map(|neighbors| neighbors.len()).max().unwrap_or(0).max(1) as f64;
+    let degree_report = eval_functions::evaluate_scorer(&test_arr, |row| {
+        let author = row[0].as_text().unwrap_or("");
+        my_graph_sim.get(author).map(|neighbors| neighbors.len()).unwrap_or(0) as f64 / max_degree
+    }, 0.5, 10);
+    degree_report.print("similarity-graph degree (held-out authors)");
+    println!("(ROC curve of the degree heuristic has {} points, PR curve {} points)", degree_report.roc.len(), degree_report.pr.len());
+
+    for group_by in [split_functions::GroupBy::Comment, split_functions::GroupBy::Author, split_functions::GroupBy::Video] {
+        let scores = split_functions::cross_validate(&df.data, 5, 210, group_by, nb_accuracy);
+        println!("Naive Bayes 5-fold accuracy grouped by {:?}: {:.3?}", group_by, scores);
+    }
+
+
+    // Label propagation: hide 20% of the users' labels and recover them from their neighbors in the similarity graph
+    let labels = propagation_functions::user_labels(&users, &spammers);
+    let holdout = propagation_functions::holdout_propagation(&my_graph_sim, &labels, 0.2, 210);
+    println!();
+    println!("Label propagation hid {} users' labels ({} of them have neighbors in the graph, threshold 0.7)", holdout.num_hidden, holdout.num_hidden_with_neighbors);
+    holdout.report.print("label propagation (hidden users)");
+
+    // Export the similarity graph for Gephi / Cytoscape / Graphviz
+    let node_attrs = export_functions::node_attributes(&df.data, &my_graph_sim, &spammers);
+    println!();
+    for (path, format) in [("similarity_graph.graphml", export_functions::GraphFormat::GraphML), ("similarity_graph.gexf", export_functions::GraphFormat::Gexf), ("similarity_graph.dot", export_functions::GraphFormat::Dot)] {
+        match export_functions::export_graph(path, format, &my_graph_sim, &my_map, &node_attrs) {
+            Ok(()) => println!("Exported the similarity graph (threshold 0.7) to {}", path),
+            Err(err) => println!("Could not export the similarity graph to {}: {}", path, err),
+        }
+    }
+
+    // Cache the similarity graph as an edge list and the spam-only graph as an adjacency matrix, then read both back
+    let sim_edges = export_functions::weighted_edges(&my_graph_sim, &my_map);
+    match export_functions::write_edge_list("similarity_graph_edges.tsv", b'\t', &my_graph_sim, &sim_edges) {
+        Ok(()) => match export_functions::read_edge_list("similarity_graph_edges.tsv", b'\t') {
+            Ok((graph, edges)) => println!("Reloaded similarity_graph_edges.tsv: {} nodes, {} edges", graph.len(), edges.len()),
+            Err(err) => println!("Could not read the edge list back: {}", err),
+        },
+        Err(err) => println!("Could not write the edge list: {}", err),
+    }
+    let spam_edges = export_functions::weighted_edges(&my_graph_spam_only, &map_of_spam);
+    let (_, spam_sparse) = export_functions::sparse_adjacency(&my_graph_spam_only, &spam_edges);
+    match export_functions::adjacency_matrix(&my_graph_spam_only, &spam_edges) {
+        Ok((spam_nodes, spam_adjacency)) => {
+            println!("Spam-only adjacency matrix: {:?}, {} nonzero entries", spam_adjacency.dim(), spam_sparse.len());
+            match export_functions::write_adjacency_matrix("spam_graph_adjacency.csv", &spam_nodes, &spam_adjacency) {
+                Ok(()) => match export_functions::read_adjacency_matrix("spam_graph_adjacency.csv") {
+                    Ok((nodes, matrix)) => {
+                        let (graph, _) = export_functions::graph_from_adjacency(&nodes, &matrix);
+                        println!("Reloaded spam_graph_adjacency.csv: {} disconnected graphs", find_num_disconnected_graphs(&graph));
+                    },
+                    Err(err) => println!("Could not read the adjacency matrix back: {}", err),
+                },
+                Err(err) => println!("Could not write the adjacency matrix: {}", err),
+            }
+        },
+        Err(err) => println!("Not writing the spam-only adjacency matrix: {}", err),
+    }
+
+    // Bipartite user <-> video graph of the spammers: who hit the same videos, and which videos share spammers
+    let spam_bipartite = bipartite_functions::BipartiteGraph::build(&df.data).restrict_to(&spammers);
+    println!();
+    println!("Spammers grouped by the exact set of videos they hit:");
+    for (videos, group) in spam_bipartite.groups_by_video_set().iter().take(5) {
+        println!("  {} spammer(s) hit {:?}", group.len(), videos);
+    }
+    println!("Videos sharing spammers (most shared first):");
+    for (video1, video2, num_shared) in spam_bipartite.video_overlap().iter().take(5) {
+        println!("  {:?} and {:?} share {} spammer(s)", video1, video2, num_shared);
+    }
+    let spammer_projection = spam_bipartite.project_users(2);
+    let video_projection = spam_bipartite.project_videos(1);
+    println!("Spammers sharing at least 2 videos form {} disconnected graphs; videos sharing a spammer form {}",
+        find_num_disconnected_graphs(&spammer_projection), find_num_disconnected_graphs(&video_projection));
+
 }
 
 
@@ -85,15 +610,25 @@ impl DataFrame {
             data: df,
             total_users: num_users,
             total_spam_users: num_spam_users,
+            spammer_policy: spam_functions::SpammerPolicy::AnySpam,
         }
     }
 
+    // purpose: change the definition of spammer the stats use
+    // input: the dataframe and the new policy
+    // output: none. total_spam_users is recounted from the data (cols 0-user, 3-class) under the policy
+    fn set_spammer_policy(&mut self, policy: spam_functions::SpammerPolicy) {
+        let (num_spam_users, _) = spam_functions::find_spam_with_policy(&self.data, policy);
+        self.total_spam_users = num_spam_users;
+        self.spammer_policy = policy;
+    }
+
     // prints some quick stats. Read the names as needed
     fn quick_stats(&self) {
         let mut counter_spam_comments = 0;
         for colval in self.data.column(3).iter() {
             match colval {
-                ColumnVal::One(_) => (),
+                ColumnVal::One(_) | ColumnVal::Three(_) => (),
                 ColumnVal::Two(some_bool) => {
                     if *some_bool {
                         counter_spam_comments += 1;
@@ -104,10 +639,139 @@ impl DataFrame {
         println!("");
         println!("Some quick stats:");
         println!("Number of total unique users: {:?}", self.total_users);
-        println!("Number of unique spam users: {:?}", self.total_spam_users);
+        println!("Number of unique spam users ({:?}): {:?}", self.spammer_policy, self.total_spam_users);
         println!("Number of spam comments in dataset: {:?}", counter_spam_comments);
         println!("");
     }
+
+    // purpose: break the quick stats down per video
+    // input: the dataframe and how many top tokens to keep per video
+    // output: a vector with one VideoStats per video, sorted by video name (re-sort it by any field with sort_by)
+    // one pass over the rows counts comments, spam comments, users, spammers and tokens per video (col 2)
+    fn video_stats(&self, num_top_tokens: usize) -> Vec<VideoStats> {
+        let mut comments: HashMap<String, (u32, u32)> = HashMap::new(); // video -> (total, spam)
+        let mut users: HashMap<String, HashSet<String>> = HashMap::new();
+        let mut spammers: HashMap<String, HashSet<String>> = HashMap::new();
+        let mut tokens: HashMap<String, HashMap<String, u32>> = HashMap::new();
+
+        for row in self.data.rows() {
+            let user = row[0].as_text().unwrap_or("").to_string();
+            let video = row[2].as_text().unwrap_or("").to_string();
+            let is_spam = row[3].as_bool().unwrap_or(false);
+
+            let counts = comments.entry(video.clone()).or_insert((0, 0));
+            counts.0 += 1;
+            spammers.entry(video.clone()).or_default();
+            if is_spam {
+                counts.1 += 1;
+                spammers.get_mut(&video).unwrap().insert(user.clone());
+            }
+            users.entry(video.clone()).or_default().insert(user);
+            let video_tokens = tokens.entry(video).or_default();
+            for token in text_functions::tokenize(row[1].as_text().unwrap_or("")) {
+                *video_tokens.entry(token).or_insert(0) += 1;
+            }
+        }
+
+        let mut stats: Vec<VideoStats> = Vec::new();
+        for (video, (total, spam)) in comments.into_iter() {
+            let mut top_tokens: Vec<(String, u32)> = tokens.remove(&video).unwrap_or_default().into_iter().collect();
+            top_tokens.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            top_tokens.truncate(num_top_tokens);
+            stats.push(VideoStats {
+                total_comments: total,
+                spam_comments: spam,
+                spam_rate: spam as f64 / total as f64,
+                unique_users: users[&video].len() as u32,
+                unique_spammers: spammers[&video].len() as u32,
+                top_tokens,
+                video,
+            });
+        }
+        stats.sort_by(|a, b| a.video.cmp(&b.video));
+        stats
+    }
+
+    // purpose: group the rows on one column (or on a date bucket) and aggregate each group
+    // input: the dataframe, what to group on, and the aggregations to compute
+    // output: a new DataFrame with one row per group (in order of first appearance). Col 0 holds the group's key and
+    // col i holds aggregations[i - 1]: One for ConcatTokens, Three for everything else. The user totals are carried over
+    // collects the row indices of each group in one pass, then computes every aggregation over each group's rows
+    fn group_by(&self, key: GroupKey, aggregations: &[Aggregation]) -> DataFrame {
+        let mut group_index: HashMap<String, usize> = HashMap::new();
+        let mut groups: Vec<(ColumnVal, Vec<usize>)> = Vec::new();
+        for i in 0..self.data.nrows() {
+            let key_val = match key {
+                GroupKey::Column(col) => self.data[[i, col]].clone(),
+                GroupKey::DateBucket(bucket) => match time_functions::row_timestamp(&self.data, i) {
+                    Some(timestamp) => ColumnVal::One(bucket.label_of(timestamp)),
+                    None => ColumnVal::One(String::new()),
+                },
+            };
+            let g = *group_index.entry(key_val.to_key()).or_insert_with(|| {
+                groups.push((key_val, Vec::new()));
+                groups.len() - 1
+            });
+            groups[g].1.push(i);
+        }
+
+        let mut giant_vec: Vec<ColumnVal> = Vec::new();
+        for (key, rows) in groups.iter() {
+            giant_vec.push(key.clone());
+            for aggregation in aggregations.iter() {
+                let value = match *aggregation {
+                    Aggregation::Count => ColumnVal::Three(rows.len() as f64),
+                    Aggregation::CountDistinct(col) => {
+                        let distinct: HashSet<String> = rows.iter().map(|i| self.data[[*i, col]].to_key()).collect();
+                        ColumnVal::Three(distinct.len() as f64)
+                    },
+                    Aggregation::SumBool(col) => {
+                        ColumnVal::Three(rows.iter().filter(|i| self.data[[**i, col]].as_bool() == Some(true)).count() as f64)
+                    },
+                    Aggregation::Mean(col) => {
+                        let numbers: Vec<f64> = rows.iter().filter_map(|i| self.data[[*i, col]].as_number()).collect();
+                        let mean = if numbers.is_empty() { f64::NAN } else { numbers.iter().sum::<f64>() / numbers.len() as f64 };
+                        ColumnVal::Three(mean)
+                    },
+                    Aggregation::ConcatTokens(col) => {
+                        let tokens: Vec<String> = rows.iter()
+                            .flat_map(|i| text_functions::tokenize(self.data[[*i, col]].as_text().unwrap_or("")))
+                            .collect();
+                        ColumnVal::One(tokens.join(" "))
+                    },
+                };
+                giant_vec.push(value);
+            }
+        }
+
+        let out_arr: Array2<ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((groups.len(), aggregations.len() + 1)).expect("Failed to reshape!");
+        let mut grouped = DataFrame::new(out_arr, self.total_users, self.total_spam_users);
+        grouped.spammer_policy = self.spammer_policy;
+        grouped
+    }
+}
+
+
+// purpose: export a per-video table to CSV
+// input: the path to write and the table
+// output: an io Result
+// top tokens are written in one cell as "token:count" pairs separated by spaces
+fn write_video_stats(path: &str, stats: &[VideoStats]) -> std::io::Result<()> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(["video", "total_comments", "spam_comments", "spam_rate", "unique_users", "unique_spammers", "top_tokens"])?;
+    for row in stats.iter() {
+        let top_tokens: Vec<String> = row.top_tokens.iter().map(|(token, count)| format!("{}:{}", token, count)).collect();
+        wtr.write_record([
+            row.video.clone(),
+            row.total_comments.to_string(),
+            row.spam_comments.to_string(),
+            format!("{:.4}", row.spam_rate),
+            row.unique_users.to_string(),
+            row.unique_spammers.to_string(),
+            top_tokens.join(" "),
+        ])?;
+    }
+    wtr.flush()
 }
 
 
@@ -129,8 +793,8 @@ fn map_users_to_words(in_df: &Array2<ColumnVal>) -> (HashMap<String, HashSet<Str
                 unique_users.insert(name.clone());
                 ()
             }
-            ColumnVal::Two(_) => {
-                println!("Should not happen, as users are not bools");
+            ColumnVal::Two(_) | ColumnVal::Three(_) => {
+                println!("Should not happen, as users are not bools or numbers");
                 ()
             }
         }
@@ -305,6 +969,51 @@ fn find_num_disconnected_graphs(graph: &HashMap<String, Vec<String>>) -> u32 {
 
 
 
+// purpose: give every user the id of the connected subgraph (community) they belong to
+// input: a graph mapping users to their neighbors
+// output: a hashmap mapping each user to a community id. Ids go 0, 1, 2, ... in the order of each community's
+// alphabetically first user, so the same graph always gets the same ids
+// same traversal as find_num_disconnected_graphs(), but starting from the keys in sorted order and recording the id
+fn find_communities(graph: &HashMap<String, Vec<String>>) -> HashMap<String, u32> {
+    let mut keys: Vec<&String> = graph.keys().collect();
+    keys.sort();
+
+    let mut communities: HashMap<String, u32> = HashMap::new();
+    let mut next_id: u32 = 0;
+    for start in keys {
+        if communities.contains_key(start) {
+            continue;
+        }
+        communities.insert(start.clone(), next_id);
+        let mut queue = VecDeque::from([start.clone()]);
+        while let Some(current) = queue.pop_front() {
+            for neighbor in graph.get(&current).map(|v| v.as_slice()).unwrap_or(&[]) {
+                if !communities.contains_key(neighbor) {
+                    communities.insert(neighbor.clone(), next_id);
+                    queue.push_back(neighbor.clone());
+                }
+            }
+        }
+        next_id += 1;
+    }
+    communities
+}
+
+
+
+// purpose: build a small array of data for a test
+// input: the rows, each (user, content, video, is spam, date, comment id); "" leaves the date or comment id empty
+// output: an array with those 6 columns, in the layout read_CSV_using_reader() loads
+#[cfg(test)]
+fn rows_to_arr(rows: &[(&str, &str, &str, bool, &str, &str)]) -> Array2<ColumnVal> {
+    let mut giant_vec: Vec<ColumnVal> = Vec::new();
+    for (user, content, video, is_spam, date, comment_id) in rows.iter() {
+        giant_vec.extend([ColumnVal::One(user.to_string()), ColumnVal::One(content.to_string()), ColumnVal::One(video.to_string()),
+                          ColumnVal::Two(*is_spam), ColumnVal::One(date.to_string()), ColumnVal::One(comment_id.to_string())]);
+    }
+    Array::from_vec(giant_vec).into_shape_with_order((rows.len(), 6)).expect("Unable to reshape")
+}
+
 #[test]
 fn test_similarity1() {
     let df: Array2<ColumnVal> = csv_functions::read_CSV_using_reader("Youtube-Spam-Dataset.csv");
@@ -429,8 +1138,892 @@ fn test_spam_arr_making() {
                 unique_spammers.insert(name.to_string());
                 ()
             },
-            ColumnVal::Two(_) => (), // should not execute
+            ColumnVal::Two(_) | ColumnVal::Three(_) => (), // should not execute
         }
     }
     assert_eq!(871, unique_spammers.len());
 }
+
+#[test]
+fn test_naive_bayes() {
+    let giant_vec: Vec<ColumnVal> = vec![ColumnVal::One("Sara".to_string()), ColumnVal::One("Pay me now".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("John".to_string()), ColumnVal::One("Subscribe and pay me".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("Teah".to_string()), ColumnVal::One("I love this video".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(false),
+                                        ColumnVal::One("Jei".to_string()), ColumnVal::One("Awesome video, love it".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(false)];
+    let my_arr: Array2<ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((4,4)).expect("Unable to reshape");
+
+    let model = bayes_functions::NaiveBayesModel::train(&my_arr, 1.0);
+    assert_eq!(2, model.spam_docs);
+    assert_eq!(2, model.ham_docs);
+    assert!(model.predict("please pay me"));
+    assert!(!model.predict("love this video"));
+    assert_eq!(0.5, model.spam_probability("completely unseen words")); // equal priors and no known tokens
+
+    // a saved model must load back to the exact same model
+    let path = std::env::temp_dir().join("test_naive_bayes_model.txt");
+    let path = path.to_str().unwrap();
+    model.save(path).expect("Unable to save model");
+    let loaded = bayes_functions::NaiveBayesModel::load(path).expect("Unable to load model");
+    assert_eq!(model, loaded);
+    std::fs::remove_file(path).unwrap();
+
+    // and it is only reused on the data and the alpha it was trained with
+    assert!(loaded.trained_on(&my_arr, 1.0));
+    assert!(!loaded.trained_on(&my_arr, 0.5));
+    let mut changed = my_arr.clone();
+    changed[[0, 3]] = ColumnVal::Two(false);
+    assert!(!loaded.trained_on(&changed, 1.0));
+}
+
+#[test]
+fn test_logistic_regression() {
+    let giant_vec: Vec<ColumnVal> = vec![ColumnVal::One("Sara".to_string()), ColumnVal::One("Pay me now".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("John".to_string()), ColumnVal::One("Subscribe and pay me".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("Teah".to_string()), ColumnVal::One("I love this video".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(false),
+                                        ColumnVal::One("Jei".to_string()), ColumnVal::One("Awesome video, love it".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(false)];
+    let my_arr: Array2<ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((4,4)).expect("Unable to reshape");
+
+    for kind in [logistic_functions::FeatureKind::BagOfWords, logistic_functions::FeatureKind::TfIdf] {
+        let features = logistic_functions::FeatureBuilder::fit(&my_arr, kind, 1);
+        assert_eq!(features.transform(&my_arr).dim(), (4, features.vocab.len()));
+
+        let model = logistic_functions::LogisticModel::train(&my_arr, features, 0.01, 0.5, 500);
+        assert!(model.predict("pay me"));
+        assert!(!model.predict("love this video"));
+
+        let coefs = model.coefficients();
+        assert!(coefs.first().unwrap().0 == "pay" || coefs.first().unwrap().0 == "me"); // tokens in both spam comments come first
+        assert!(coefs.last().unwrap().0 == "love" || coefs.last().unwrap().0 == "video"); // tokens in both ham comments come last
+    }
+}
+
+#[test]
+fn test_k_fold_by_author() {
+    let df: Array2<ColumnVal> = csv_functions::read_CSV_using_reader("Youtube-Spam-Dataset.csv");
+    let folds = split_functions::stratified_k_fold(&df, 5, 7, split_functions::GroupBy::Author);
+    assert_eq!(5, folds.len());
+
+    let mut total_test_rows = 0;
+    for (train, test) in folds.iter() {
+        total_test_rows += test.nrows();
+        assert_eq!(df.nrows(), train.nrows() + test.nrows());
+
+        // no author may appear on both sides of a fold
+        let train_authors: HashSet<String> = train.column(0).iter().map(|cell| cell.as_text().unwrap().to_string()).collect();
+        assert!(test.column(0).iter().all(|cell| !train_authors.contains(cell.as_text().unwrap())));
+
+        // stratification keeps spam in every test fold
+        assert!(test.column(3).iter().any(|cell| cell.as_bool().unwrap()));
+    }
+    assert_eq!(df.nrows(), total_test_rows);
+
+    // the same seed gives the same split, and the split sizes follow the fraction
+    let (train1, test1) = split_functions::train_test_split(&df, 0.25, 3, split_functions::GroupBy::Comment);
+    let (train2, test2) = split_functions::train_test_split(&df, 0.25, 3, split_functions::GroupBy::Comment);
+    assert_eq!(train1, train2);
+    assert_eq!(test1, test2);
+    assert!((test1.nrows() as f64 / df.nrows() as f64 - 0.25).abs() < 0.01);
+}
+
+#[test]
+fn test_evaluation_metrics() {
+    let predicted = vec![true, true, false, false, true];
+    let actual = vec![true, false, false, true, true];
+    let matrix = eval_functions::ConfusionMatrix::from_predictions(&predicted, &actual);
+    assert_eq!((2, 1, 1, 1), (matrix.true_pos, matrix.false_pos, matrix.true_neg, matrix.false_neg));
+    assert_eq!(3.0 / 5.0, matrix.accuracy());
+    assert_eq!(2.0 / 3.0, matrix.precision());
+    assert_eq!(2.0 / 3.0, matrix.recall());
+    assert!((matrix.f1() - 2.0 / 3.0).abs() < 1e-12);
+
+    // a perfect ranking has an ROC AUC and average precision of 1, a fully reversed one has an ROC AUC of 0
+    let labels = vec![true, true, false, false];
+    let perfect = vec![0.9, 0.8, 0.3, 0.1];
+    let reversed = vec![0.1, 0.2, 0.8, 0.9];
+    assert_eq!(1.0, eval_functions::auc(&eval_functions::roc_curve(&perfect, &labels)));
+    assert_eq!(1.0, eval_functions::average_precision(&eval_functions::pr_curve(&perfect, &labels)));
+    assert_eq!(0.0, eval_functions::auc(&eval_functions::roc_curve(&reversed, &labels)));
+    // all scores tied is no better than chance
+    assert_eq!(0.5, eval_functions::auc(&eval_functions::roc_curve(&[0.5; 4], &labels)));
+
+    let bins = eval_functions::calibration_bins(&perfect, &labels, 2);
+    assert_eq!(2, bins[0].count);
+    assert_eq!(0.0, bins[0].spam_rate);
+    assert_eq!(1.0, bins[1].spam_rate);
+    assert!((bins[1].mean_score - 0.85).abs() < 1e-12);
+}
+
+#[test]
+fn test_label_propagation() {
+    // A (spam) - B - C (ham), and E hangs off A only. D is alone
+    let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+    graph.insert("A".to_string(), vec!["B".to_string(), "E".to_string()]);
+    graph.insert("B".to_string(), vec!["A".to_string(), "C".to_string()]);
+    graph.insert("C".to_string(), vec!["B".to_string()]);
+    graph.insert("D".to_string(), vec![]);
+    graph.insert("E".to_string(), vec!["A".to_string()]);
+
+    let users = vec!["A".to_string(), "C".to_string()];
+    let spammers = vec!["A".to_string()];
+    let seeds = propagation_functions::user_labels(&users, &spammers);
+    let scores = propagation_functions::propagate_labels(&graph, &seeds, 100, 1e-9);
+
+    assert_eq!(1.0, scores["A"]);
+    assert_eq!(0.0, scores["C"]);
+    assert!((scores["B"] - 0.5).abs() < 1e-6); // halfway between a spammer and a non-spammer
+    assert!((scores["E"] - 1.0).abs() < 1e-6); // only connected to a spammer
+    assert_eq!(0.5, scores["D"]); // unreachable, keeps the seeds' spam rate
+
+    // hiding labels is seeded, so two runs agree
+    let all_labels = propagation_functions::user_labels(&["A".to_string(), "B".to_string(), "C".to_string(), "D".to_string(), "E".to_string()], &spammers);
+    let run1 = propagation_functions::holdout_propagation(&graph, &all_labels, 0.4, 1);
+    let run2 = propagation_functions::holdout_propagation(&graph, &all_labels, 0.4, 1);
+    assert_eq!(2, run1.num_hidden);
+    assert_eq!(run1.report.confusion, run2.report.confusion);
+}
+
+#[test]
+fn test_graph_export() {
+    let giant_vec: Vec<ColumnVal> = vec![ColumnVal::One("Sara".to_string()), ColumnVal::One("apple banana".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("John".to_string()), ColumnVal::One("apple banana".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(false),
+                                        ColumnVal::One("Sara".to_string()), ColumnVal::One("banana".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(false),
+                                        ColumnVal::One("T&<\"ah".to_string()), ColumnVal::One("cabbage".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(false)];
+    let my_arr: Array2<ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((4,4)).expect("Unable to reshape");
+    let (my_map, users) = map_users_to_words(&my_arr);
+    let graph = create_graph(&users, &my_map, 1.0);
+    let spammers = vec!["Sara".to_string()];
+
+    let communities = find_communities(&graph);
+    assert_eq!(communities["John"], communities["Sara"]);
+    assert_ne!(communities["John"], communities["T&<\"ah"]);
+
+    let attrs = export_functions::node_attributes(&my_arr, &graph, &spammers);
+    assert_eq!(2, attrs["Sara"].comment_count);
+    assert_eq!(vec!["Video1".to_string(), "Video2".to_string()], attrs["Sara"].videos);
+    assert!(attrs["Sara"].is_spammer && !attrs["John"].is_spammer);
+
+    let edges = export_functions::weighted_edges(&graph, &my_map);
+    assert_eq!(vec![("John".to_string(), "Sara".to_string(), 1.0)], edges);
+
+    let graphml = export_functions::to_graphml(&graph, &attrs, &edges);
+    assert!(graphml.contains("<data key=\"name\">T&amp;&lt;&quot;ah</data>"));
+    assert!(graphml.contains("<data key=\"weight\">1</data>"));
+    assert_eq!(3, graphml.matches("<node ").count());
+
+    let gexf = export_functions::to_gexf(&graph, &attrs, &edges);
+    assert!(gexf.contains("weight=\"1\""));
+    assert_eq!(1, gexf.matches("<edge ").count());
+
+    let dot = export_functions::to_dot(&graph, &attrs, &edges);
+    assert!(dot.contains("\"John\" -- \"Sara\" [weight=1];"));
+    assert!(dot.contains("\"T&<\\\"ah\""));
+}
+
+#[test]
+fn test_edge_list_and_adjacency() {
+    let mut my_map: HashMap<String, HashSet<String>> = HashMap::new();
+    my_map.insert("Sara".to_string(), HashSet::from(["apple".to_string(), "banana".to_string()]));
+    my_map.insert("Jo, \"Jr\"".to_string(), HashSet::from(["apple".to_string(), "banana".to_string(), "kiwi".to_string()]));
+    my_map.insert("Teah".to_string(), HashSet::from(["cabbage".to_string()]));
+    let users: Vec<String> = vec!["Sara".to_string(), "Jo, \"Jr\"".to_string(), "Teah".to_string()];
+    let graph = create_graph(&users, &my_map, 0.5);
+    let edges = export_functions::weighted_edges(&graph, &my_map);
+
+    // an edge list round trip keeps the isolated node and the weight, even with a delimiter and quotes in a name
+    let path = std::env::temp_dir().join("test_edge_list.csv");
+    let path = path.to_str().unwrap();
+    export_functions::write_edge_list(path, b',', &graph, &edges).expect("Unable to write edge list");
+    let (graph2, edges2) = export_functions::read_edge_list(path, b',').expect("Unable to read edge list");
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(edges, edges2);
+    assert_eq!(3, graph2.len());
+    assert!(graph2["Teah"].is_empty());
+
+    let (nodes, matrix) = export_functions::adjacency_matrix(&graph, &edges).expect("no edge has weight 0");
+    assert_eq!(vec!["Jo, \"Jr\"".to_string(), "Sara".to_string(), "Teah".to_string()], nodes);
+    assert_eq!(2.0 / 3.0, matrix[[0, 1]]);
+    assert_eq!(matrix, matrix.t());
+    assert_eq!(vec![(0, 1, 2.0 / 3.0), (1, 0, 2.0 / 3.0)], export_functions::sparse_adjacency(&graph, &edges).1);
+
+    // a dense matrix round trip through a file gives back the same graph
+    let path = std::env::temp_dir().join("test_adjacency.csv");
+    let path = path.to_str().unwrap();
+    export_functions::write_adjacency_matrix(path, &nodes, &matrix).expect("Unable to write matrix");
+    let (nodes2, matrix2) = export_functions::read_adjacency_matrix(path).expect("Unable to read matrix");
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(nodes, nodes2);
+    assert_eq!(matrix, matrix2);
+    let (graph3, edges3) = export_functions::graph_from_adjacency(&nodes2, &matrix2);
+    assert_eq!(edges, edges3);
+    assert_eq!(graph3["Sara"], vec!["Jo, \"Jr\"".to_string()]);
+
+    // with threshold 0.0 Teah is linked to both with weight 0: the edge list keeps those edges, the dense matrix refuses them
+    let graph_all = create_graph(&users, &my_map, 0.0);
+    let edges_all = export_functions::weighted_edges(&graph_all, &my_map);
+    assert_eq!(3, edges_all.len());
+    let path = std::env::temp_dir().join("test_edge_list_zero.tsv");
+    let path = path.to_str().unwrap();
+    export_functions::write_edge_list(path, b'\t', &graph_all, &edges_all).expect("Unable to write edge list");
+    let (graph4, edges4) = export_functions::read_edge_list(path, b'\t').expect("Unable to read edge list");
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(edges_all, edges4);
+    assert_eq!(2, graph4["Teah"].len());
+    assert!(export_functions::adjacency_matrix(&graph_all, &edges_all).is_err());
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let mut user_words: HashMap<String, HashSet<String>> = HashMap::new();
+    user_words.insert("Sara".to_string(), HashSet::from(["apple".to_string(), "banana".to_string()]));
+    user_words.insert("Сергей Андреевич".to_string(), HashSet::from(["привет".to_string()]));
+    let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+    graph.insert("Sara".to_string(), vec![]);
+    graph.insert("Сергей Андреевич".to_string(), vec![]);
+    let rows = [("Sara", "apple banana", "Video1", true, "", ""), ("Сергей Андреевич", "привет", "Video1", false, "", "")];
+    let mut my_arr: Array2<ColumnVal> = rows_to_arr(&rows);
+    let snap = snapshot_functions::Snapshot {
+        source_rows: 2,
+        data_hash: csv_functions::data_hash(&my_arr),
+        threshold: 0.7,
+        users: vec!["Сергей Андреевич".to_string(), "Sara".to_string()],
+        user_words,
+        graph,
+        spammers: vec!["Sara".to_string()],
+    };
+
+    let path = std::env::temp_dir().join("test_snapshot.bin");
+    let path = path.to_str().unwrap();
+    snap.save(path).expect("Unable to save snapshot");
+    assert_eq!(snap, snapshot_functions::Snapshot::load(path).expect("Unable to load snapshot"));
+
+    // it only matches the data it was built from: editing one comment (same # of rows) or the threshold makes it stale
+    assert!(snap.matches(&my_arr, 0.7));
+    assert!(!snap.matches(&my_arr, 0.5));
+    my_arr[[0, 1]] = ColumnVal::One("apple cherry".to_string());
+    assert!(!snap.matches(&my_arr, 0.7));
+
+    // a snapshot from another format version is refused
+    let mut bytes = std::fs::read(path).unwrap();
+    bytes[8..12].copy_from_slice(&(snapshot_functions::SNAPSHOT_VERSION + 1).to_le_bytes());
+    std::fs::write(path, &bytes).unwrap();
+    assert!(snapshot_functions::Snapshot::load(path).unwrap_err().contains("version"));
+
+    // and so are damaged files
+    bytes[8..12].copy_from_slice(&snapshot_functions::SNAPSHOT_VERSION.to_le_bytes());
+    bytes.truncate(bytes.len() - 3);
+    std::fs::write(path, &bytes).unwrap();
+    assert!(snapshot_functions::Snapshot::load(path).is_err());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_bipartite_projection() {
+    let giant_vec: Vec<ColumnVal> = vec![ColumnVal::One("Sara".to_string()), ColumnVal::One("Pay me".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("Sara".to_string()), ColumnVal::One("Pay me".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("John".to_string()), ColumnVal::One("Pay me".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("John".to_string()), ColumnVal::One("Pay me".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("Teah".to_string()), ColumnVal::One("Nice".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(false),
+                                        ColumnVal::One("Maya".to_string()), ColumnVal::One("Subscribe".to_string()), ColumnVal::One("Video3".to_string()), ColumnVal::Two(true)];
+    let my_arr: Array2<ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((6,4)).expect("Unable to reshape");
+    let bipartite = bipartite_functions::BipartiteGraph::build(&my_arr);
+
+    let users = bipartite.project_users(1);
+    assert_eq!(vec!["Sara".to_string(), "Teah".to_string()], users["John"]);
+    assert!(users["Maya"].is_empty());
+    let users2 = bipartite.project_users(2);
+    assert_eq!(vec!["John".to_string()], users2["Sara"]);
+    assert!(users2["Teah"].is_empty());
+
+    let videos = bipartite.project_videos(1);
+    assert_eq!(vec!["Video2".to_string()], videos["Video1"]);
+    assert!(videos["Video3"].is_empty());
+
+    let spam_only = bipartite.restrict_to(&["Sara".to_string(), "John".to_string(), "Maya".to_string()]);
+    assert_eq!(vec![("Video1".to_string(), "Video2".to_string(), 2)], spam_only.video_overlap());
+    let groups = spam_only.groups_by_video_set();
+    assert_eq!((vec!["Video1".to_string(), "Video2".to_string()], vec!["John".to_string(), "Sara".to_string()]), groups[0]);
+    assert_eq!(2, groups.len());
+}
+
+#[test]
+fn test_video_stats() {
+    let giant_vec: Vec<ColumnVal> = vec![ColumnVal::One("Sara".to_string()), ColumnVal::One("Pay me".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("John".to_string()), ColumnVal::One("Pay me now".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("Teah".to_string()), ColumnVal::One("I love this video".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(false),
+                                        ColumnVal::One("Sara".to_string()), ColumnVal::One("This is cool".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(false),
+                                        ColumnVal::One("Sara".to_string()), ColumnVal::One("Pay me".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true)];
+    let my_arr: Array2<ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((5,4)).expect("Unable to reshape");
+    let df = DataFrame::new(my_arr, 3, 2);
+
+    let mut stats = df.video_stats(2);
+    assert_eq!(2, stats.len());
+    assert_eq!(VideoStats {
+        video: "Video1".to_string(),
+        total_comments: 4,
+        spam_comments: 3,
+        spam_rate: 0.75,
+        unique_users: 3,
+        unique_spammers: 2,
+        top_tokens: vec![("me".to_string(), 3), ("pay".to_string(), 3)],
+    }, stats[0]);
+    assert_eq!(0, stats[1].unique_spammers);
+
+    stats.sort_by_key(|row| row.total_comments);
+    assert_eq!("Video2", stats[0].video);
+
+    let path = std::env::temp_dir().join("test_video_stats.csv");
+    let path = path.to_str().unwrap();
+    write_video_stats(path, &stats).expect("Unable to write video stats");
+    let text = std::fs::read_to_string(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert!(text.contains("Video1,4,3,0.7500,3,2,me:3 pay:3"));
+}
+
+#[test]
+fn test_group_by() {
+    let giant_vec: Vec<ColumnVal> = vec![ColumnVal::One("Sara".to_string()), ColumnVal::One("Pay me".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("John".to_string()), ColumnVal::One("Pay me now".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("Teah".to_string()), ColumnVal::One("I love this video".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(false),
+                                        ColumnVal::One("Sara".to_string()), ColumnVal::One("This is cool!".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(false)];
+    let my_arr: Array2<ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((4,4)).expect("Unable to reshape");
+    let df = DataFrame::new(my_arr, 3, 2);
+
+    let by_author = df.group_by(GroupKey::Column(0), &[Aggregation::Count, Aggregation::CountDistinct(2), Aggregation::SumBool(3), Aggregation::Mean(3), Aggregation::ConcatTokens(1)]);
+    assert_eq!((3, 6), by_author.data.dim());
+    assert_eq!(3, by_author.total_users); // carried over
+    assert_eq!(vec![ColumnVal::One("Sara".to_string()), ColumnVal::Three(2.0), ColumnVal::Three(2.0), ColumnVal::Three(1.0), ColumnVal::Three(0.5), ColumnVal::One("pay me this is cool".to_string())],
+        by_author.data.row(0).to_vec());
+    assert_eq!(ColumnVal::One("John".to_string()), by_author.data[[1, 0]]); // groups keep first-appearance order
+
+    // grouping on the label column works the same way, and the result can be grouped again
+    let by_label = df.group_by(GroupKey::Column(3), &[Aggregation::Count, Aggregation::CountDistinct(0)]);
+    assert_eq!(vec![ColumnVal::Two(true), ColumnVal::Three(2.0), ColumnVal::Three(2.0)], by_label.data.row(0).to_vec());
+    let by_count = by_author.group_by(GroupKey::Column(1), &[Aggregation::Count]);
+    assert_eq!(vec![ColumnVal::Three(2.0), ColumnVal::Three(1.0)], by_count.data.row(0).to_vec());
+
+    // the mean of a text column has no numbers to average
+    assert!(df.group_by(GroupKey::Column(2), &[Aggregation::Mean(1)]).data[[0, 1]].as_number().unwrap().is_nan());
+}
+
+#[test]
+fn test_temporal_analysis() {
+    assert_eq!(Some(1383805248), time_functions::parse_timestamp("2013-11-07T06:20:48"));
+    assert_eq!(Some(1390231667), time_functions::parse_timestamp("2014-01-20T15:27:47.271000"));
+    assert_eq!(None, time_functions::parse_timestamp(""));
+    assert_eq!("2013-11-04", time_functions::TimeBucket::Weekly.label_of(1383805248)); // that Thursday's week starts on Monday the 4th
+    assert_eq!("2013-11-07 06:00", time_functions::TimeBucket::Hourly.label_of(1383805248));
+    assert_eq!("2013-11-07T06:20:48", time_functions::format_timestamp(1383805248));
+
+    let giant_vec: Vec<ColumnVal> = vec![ColumnVal::One("Sara".to_string()), ColumnVal::One("Pay me now".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true), ColumnVal::One("2014-01-01T10:00:00".to_string()),
+                                        ColumnVal::One("Sara".to_string()), ColumnVal::One("Pay me now please".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true), ColumnVal::One("2014-01-01T10:10:00".to_string()),
+                                        ColumnVal::One("Sara".to_string()), ColumnVal::One("Pay me now".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(true), ColumnVal::One("2014-01-01T10:20:00".to_string()),
+                                        ColumnVal::One("John".to_string()), ColumnVal::One("Nice song".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(false), ColumnVal::One("2014-01-02T08:00:00".to_string()),
+                                        ColumnVal::One("John".to_string()), ColumnVal::One("I love it".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(false), ColumnVal::One("2014-01-02T08:05:00".to_string()),
+                                        ColumnVal::One("John".to_string()), ColumnVal::One("Great beat".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(false), ColumnVal::One("2014-01-02T08:06:00".to_string()),
+                                        ColumnVal::One("Teah".to_string()), ColumnVal::One("Wow".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(false), ColumnVal::One("".to_string())];
+    let my_arr: Array2<ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((7,5)).expect("Unable to reshape");
+
+    let daily = time_functions::time_series(&my_arr, time_functions::TimeBucket::Daily, None);
+    assert_eq!(2, daily.len()); // the undated row is left out
+    assert_eq!(("2014-01-01".to_string(), 3, 1.0), (daily[0].label.clone(), daily[0].comments, daily[0].spam_rate));
+    let video2 = time_functions::time_series(&my_arr, time_functions::TimeBucket::Daily, Some("Video2"));
+    assert_eq!(1, video2.len());
+    assert_eq!(2, time_functions::time_series_by_video(&my_arr, time_functions::TimeBucket::Weekly).len());
+
+    // Sara's three near-identical comments in 20 minutes are a burst; John's three different comments are not
+    let bursts = time_functions::detect_bursts(&my_arr, None, 3_600, 3, 0.5);
+    assert_eq!(1, bursts.len());
+    assert_eq!(("Sara".to_string(), vec![0, 1, 2]), (bursts[0].key.clone(), bursts[0].rows.clone()));
+
+    // grouped by cluster, Sara and John together post 6 comments within a day
+    let clusters: HashMap<String, u32> = HashMap::from([("Sara".to_string(), 0), ("John".to_string(), 0)]);
+    let cluster_bursts = time_functions::detect_bursts(&my_arr, Some(&clusters), 2 * 86_400, 6, 0.0);
+    assert_eq!("cluster 0", cluster_bursts[0].key);
+    // a cluster of one user would only repeat that user's bursts, so Sara's burst isn't reported again as "cluster 1"
+    let singletons: HashMap<String, u32> = HashMap::from([("Sara".to_string(), 1), ("John".to_string(), 2)]);
+    assert!(time_functions::detect_bursts(&my_arr, Some(&singletons), 3_600, 3, 0.5).is_empty());
+
+    // Maya's three near-identical comments are found even though unrelated ones follow within minutes; Rob posts the
+    // same text every 10 minutes, so 3 comments in 20 minutes is just his usual rate and not a burst
+    let rows = [("Maya", "subscribe to my channel now", "Video1", true, "2014-01-03T10:00:00", ""),
+                ("Maya", "subscribe to my channel now", "Video1", true, "2014-01-03T10:01:00", ""),
+                ("Maya", "subscribe to my channel now!", "Video1", true, "2014-01-03T10:02:00", ""),
+                ("Maya", "nice song", "Video1", true, "2014-01-03T10:03:00", ""),
+                ("Maya", "great beat", "Video1", true, "2014-01-03T10:04:00", ""),
+                ("Maya", "love the dancing", "Video1", true, "2014-01-03T10:05:00", ""),
+                ("Maya", "hello", "Video1", true, "2014-01-05T10:00:00", ""),
+                ("Maya", "so good", "Video1", true, "2014-01-06T10:00:00", ""),
+                ("Maya", "wow", "Video1", true, "2014-01-07T10:00:00", ""),
+                ("Rob", "pay me now", "Video1", true, "2014-01-04T10:00:00", ""),
+                ("Rob", "pay me now", "Video1", true, "2014-01-04T10:10:00", ""),
+                ("Rob", "pay me now", "Video1", true, "2014-01-04T10:20:00", ""),
+                ("Rob", "pay me now", "Video1", true, "2014-01-04T10:30:00", ""),
+                ("Rob", "pay me now", "Video1", true, "2014-01-04T10:40:00", ""),
+                ("Rob", "pay me now", "Video1", true, "2014-01-04T10:50:00", "")];
+    let streams: Array2<ColumnVal> = rows_to_arr(&rows);
+    let bursts = time_functions::detect_bursts(&streams, None, 1_200, 3, 0.6);
+    assert_eq!(1, bursts.len());
+    assert_eq!(("Maya".to_string(), vec![0, 1, 2]), (bursts[0].key.clone(), bursts[0].rows.clone()));
+
+    let df = DataFrame::new(my_arr, 3, 1);
+    let by_day = df.group_by(GroupKey::DateBucket(time_functions::TimeBucket::Daily), &[Aggregation::Count]);
+    assert_eq!(vec![ColumnVal::One("2014-01-01".to_string()), ColumnVal::Three(3.0)], by_day.data.row(0).to_vec());
+    assert_eq!(vec![ColumnVal::One("".to_string()), ColumnVal::Three(1.0)], by_day.data.row(2).to_vec());
+}
+
+#[test]
+fn test_window_graphs() {
+    // two non-overlapping 2-day windows: pairs {A,B} and {C,D} in the first, {A,C} in the second, {E,F} in the third
+    let rows = [("A", "pay me now", "Video1", true, "2014-01-01T10:00:00", ""),
+                ("B", "pay me now", "Video1", true, "2014-01-01T11:00:00", ""),
+                ("C", "buy cheap pills", "Video1", true, "2014-01-02T10:00:00", ""),
+                ("D", "buy cheap pills", "Video1", true, "2014-01-02T12:00:00", ""),
+                ("A", "join my channel", "Video1", true, "2014-01-03T10:00:00", ""),
+                ("C", "join my channel", "Video1", true, "2014-01-04T10:00:00", ""),
+                ("E", "hello there", "Video1", false, "2014-01-05T10:00:00", ""),
+                ("F", "hello there", "Video1", false, "2014-01-06T10:00:00", "")];
+    let my_arr: Array2<ColumnVal> = rows_to_arr(&rows);
+
+    let windows = window_functions::build_window_graphs(&my_arr, 2, 2, 0.9, None);
+    assert_eq!(3, windows.len());
+    assert_eq!(vec![vec!["A".to_string(), "B".to_string()], vec!["C".to_string(), "D".to_string()]], windows[0].communities);
+    assert_eq!(vec!["A".to_string(), "B".to_string(), "C".to_string(), "D".to_string()], windows[0].top_spammers);
+    assert_eq!(vec![vec!["A".to_string(), "C".to_string()]], windows[1].communities);
+    assert!(windows[2].top_spammers.is_empty()); // E and F are not spammers
+    // given the users' languages, A and B (told apart as different languages) are not linked
+    let languages: HashMap<String, String> = HashMap::from([("A".to_string(), "en".to_string()), ("B".to_string(), "de".to_string())]);
+    let windows_same_language = window_functions::build_window_graphs(&my_arr, 2, 2, 0.9, Some(&languages));
+    assert_eq!(vec![vec!["C".to_string(), "D".to_string()]], windows_same_language[0].communities);
+
+    let events = window_functions::community_events(&windows);
+    assert_eq!(5, events.len());
+    // both communities of the first window are born there
+    assert_eq!((window_functions::EventKind::Birth, 0, vec![0]), (events[0].kind, events[0].window, events[0].after.clone()));
+    assert_eq!((window_functions::EventKind::Birth, 0, vec![1]), (events[1].kind, events[1].window, events[1].after.clone()));
+    assert!(events[0].before.is_empty());
+    assert_eq!((window_functions::EventKind::Merge, 1, vec![0, 1]), (events[2].kind, events[2].window, events[2].before.clone()));
+    assert_eq!((window_functions::EventKind::Death, 2), (events[3].kind, events[3].window));
+    assert_eq!((window_functions::EventKind::Birth, 2, vec![0]), (events[4].kind, events[4].window, events[4].after.clone()));
+}
+
+#[test]
+fn test_near_duplicates() {
+    let rows = [("Sara", "Check out my channel please!!", "Video1", true, "", "c1"),
+                ("John", "check out my channel please", "Video2", true, "", "c2"),
+                ("Teah", "Check out my channel please :)", "Video1", true, "", "c3"),
+                ("Mike", "I love this song so much", "Video1", false, "", "c4"),
+                ("Mike", "What a great video", "Video2", false, "", "c5"),
+                ("Anna", "", "Video2", true, "", "c6")];
+    let my_arr: Array2<ColumnVal> = rows_to_arr(&rows);
+
+    assert_eq!(duplicate_functions::shingles("Check out", 3), duplicate_functions::shingles("CHECK out!", 3));
+    assert!(duplicate_functions::shingles("!!", 3).is_empty());
+    let sig = duplicate_functions::minhash_signature(&duplicate_functions::shingles("pay me now", 3), 16);
+    assert_eq!(sig, duplicate_functions::minhash_signature(&duplicate_functions::shingles("Pay me now", 3), 16));
+
+    let clusters = duplicate_functions::find_near_duplicates(&my_arr, 4, 20, 2, 0.8);
+    assert_eq!(1, clusters.len()); // the empty comment and Mike's two different comments are not near-duplicates
+    assert_eq!(vec!["c1".to_string(), "c2".to_string(), "c3".to_string()], clusters[0].comment_ids);
+    assert_eq!(vec!["John".to_string(), "Sara".to_string(), "Teah".to_string()], clusters[0].authors);
+    assert_eq!(vec!["Video1".to_string(), "Video2".to_string()], clusters[0].videos);
+
+    // without the comment id column the rows are named by index
+    assert_eq!("row 2", duplicate_functions::row_comment_id(&my_arr.slice(ndarray::s![.., 0..5]).to_owned(), 2));
+
+    let path = std::env::temp_dir().join("test_near_duplicates.csv");
+    let path = path.to_str().unwrap();
+    duplicate_functions::write_duplicate_clusters(path, &my_arr, &clusters).expect("Unable to write clusters");
+    let text = std::fs::read_to_string(path).unwrap();
+    assert_eq!(4, text.lines().count());
+    assert!(text.lines().nth(1).unwrap().starts_with("0,c1,Sara,Video1,"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_coordination_report() {
+    // Sara and John post the same rare pitch minutes apart on the same video; Teah joins them on a second video
+    let rows = [("Sara", "visit zqxbargains today", "Video1", true, "2014-01-01T10:00:00", ""),
+                ("John", "visit zqxbargains now", "Video1", true, "2014-01-01T10:04:00", ""),
+                ("Teah", "zqxbargains visit for deals", "Video2", true, "2014-01-01T10:30:00", ""),
+                ("John", "zqxbargains rocks", "Video2", true, "2014-01-01T10:33:00", ""),
+                ("Mike", "nice song", "Video1", true, "2014-02-01T10:00:00", ""),
+                ("Anna", "great song", "Video3", true, "2014-03-01T10:00:00", "")];
+    let my_arr: Array2<ColumnVal> = rows_to_arr(&rows);
+    let (map, users) = map_users_to_words(&my_arr);
+    let graph = create_graph(&users, &map, 0.3);
+
+    let idf = text_functions::user_idf(&map);
+    assert!((idf["visit"] - (5.0_f64 / 3.0).ln()).abs() < 1e-12);
+    assert_eq!(2, text_functions::token_user_counts(&map)["song"]);
+
+    let report = coordination_functions::coordination_report(&my_arr, &graph, &map, 10, 3, 0.3);
+    let top = &report.pairs[0];
+    assert_eq!(("John", "Sara"), (top.user1.as_str(), top.user2.as_str()));
+    assert_eq!(vec!["Video1".to_string()], top.shared_videos);
+    assert_eq!(1, top.close_posts);
+    assert!(top.shared_rare_tokens.iter().any(|(token, _)| token == "zqxbargains"));
+    assert!(report.pairs.iter().all(|p| p.user1 != "Anna" && p.user2 != "Anna")); // Anna shares nothing suspicious
+
+    assert_eq!(1, report.groups.len());
+    assert_eq!(vec!["John".to_string(), "Sara".to_string(), "Teah".to_string()], report.groups[0].users);
+    assert!(report.groups[0].rare_tokens.contains(&("zqxbargains".to_string(), 3)));
+}
+
+#[test]
+fn test_explain_pair() {
+    let rows = [("Sara", "Visit zqxbargains now!", "Video1", true, "", "c1"),
+                ("Sara", "I love this", "Video1", true, "", "c2"),
+                ("John", "visit zqxbargains today", "Video1", true, "", "c3"),
+                ("Teah", "visit my page", "Video1", true, "", "c4")];
+    let my_arr: Array2<ColumnVal> = rows_to_arr(&rows);
+    let (map, _) = map_users_to_words(&my_arr);
+
+    let explanation = explain_functions::explain_pair(&my_arr, &map, "Sara", "John").unwrap();
+    assert_eq!(Some(explanation.similarity), find_similarities("Sara".to_string(), "John".to_string(), &map));
+    // zqxbargains (2 of 3 users) is rarer than visit (all 3 users, idf 0), so it carries the whole weighted similarity
+    assert_eq!("zqxbargains", explanation.shared_tokens[0].token);
+    assert_eq!((0.0, 0.0), (explanation.shared_tokens[1].idf, explanation.shared_tokens[1].contribution));
+    assert!((explanation.weighted_similarity - explanation.shared_tokens[0].contribution).abs() < 1e-12);
+    assert_eq!(vec!["i", "love", "now", "this"], explanation.only_user1);
+    assert_eq!(vec!["today"], explanation.only_user2);
+    assert_eq!(vec![0, 2], explanation.comments.iter().map(|c| c.row).collect::<Vec<usize>>()); // "I love this" shares nothing
+    assert_eq!(vec!["visit", "zqxbargains"], explanation.comments[0].shared_tokens);
+    assert_eq!("c1", explanation.comments[0].comment_id);
+
+    assert!(explain_functions::explain_pair(&my_arr, &map, "Sara", "Nobody").is_err());
+}
+
+#[test]
+fn test_vocabulary_ranking() {
+    let rows = [("User", "subscribe to my channel", "Video1", true, "", ""),
+                ("User", "please subscribe my channel", "Video1", true, "", ""),
+                ("User", "subscribe for free stuff", "Video1", true, "", ""),
+                ("User", "i love this song", "Video1", false, "", ""),
+                ("User", "this song is my favorite", "Video1", false, "", ""),
+                ("User", "love it", "Video1", false, "", "")];
+    let my_arr: Array2<ColumnVal> = rows_to_arr(&rows);
+
+    let vocab = vocab_functions::score_vocabulary(&my_arr, 2);
+    assert_eq!((3, 3), (vocab.spam_docs, vocab.ham_docs));
+    assert_eq!(vec!["channel", "love", "my", "song", "subscribe", "this"], vocab.scores.iter().map(|s| s.token.as_str()).collect::<Vec<&str>>());
+
+    for method in [vocab_functions::VocabScore::LogOdds, vocab_functions::VocabScore::ChiSquare, vocab_functions::VocabScore::Pmi] {
+        // PMI only looks at whether the token ever shows up in ham, so "channel" ties with "subscribe" and wins by name
+        let expected = if method == vocab_functions::VocabScore::Pmi { "channel" } else { "subscribe" };
+        assert_eq!(expected, vocab.top_tokens(method, true, 1)[0].token);
+        assert!(vocab.top_tokens(method, false, 3).iter().all(|s| s.spam_docs == 0)); // love, song, this
+    }
+    let subscribe = &vocab.scores[4];
+    assert_eq!((3, 0, 3, 0), (subscribe.spam_count, subscribe.ham_count, subscribe.spam_docs, subscribe.ham_docs));
+    assert!((subscribe.chi_square - 6.0).abs() < 1e-9); // a perfect split of 6 comments
+    assert!((subscribe.pmi_spam - 2.0_f64.ln()).abs() < 1e-9);
+    // "my" is in 2 spam and 1 ham comment: a weak spam lean, so it is not among the ham tokens by chi-square
+    let my = &vocab.scores[2];
+    assert!(my.log_odds > 0.0 && my.log_odds < subscribe.log_odds);
+    assert!(vocab.top_tokens(vocab_functions::VocabScore::ChiSquare, false, 10).iter().all(|s| s.token != "my"));
+}
+
+#[test]
+fn test_link_extraction() {
+    let links = link_functions::extract_links("Check <a href=\"http://www.Example.com/shop?a=1&amp;b=2\">this</a>, and youtu.be/9bZkp7q19f0! Follow @Sara_M. Mail me at a@b.com");
+    assert_eq!(3, links.len());
+    assert_eq!((link_functions::LinkKind::Url, "http://www.Example.com/shop?a=1&b=2", Some("example.com".to_string())),
+               (links[0].kind, links[0].text.as_str(), links[0].domain.clone()));
+    assert_eq!((link_functions::LinkKind::YouTubeVideo, Some("9bZkp7q19f0".to_string())), (links[1].kind, links[1].target.clone()));
+    assert_eq!((link_functions::LinkKind::Handle, Some("sara_m".to_string()), None), (links[2].kind, links[2].target.clone(), links[2].domain.clone()));
+
+    let channel = link_functions::extract_links("https://www.youtube.com/user/SomeSinger/videos");
+    assert_eq!((link_functions::LinkKind::YouTubeChannel, Some("user/SomeSinger".to_string())), (channel[0].kind, channel[0].target.clone()));
+    assert_eq!(Some("9bZkp7q19f0".to_string()), link_functions::extract_links("youtube.com/watch?v=9bZkp7q19f0&t=10")[0].target);
+    assert!(link_functions::extract_links("Hi.How are you... costs 9.99 e.g. this").is_empty());
+
+    let rows = [("Sara", "visit example.com now", "Video1", true, "", ""),
+                ("Sara", "www.example.com/deals and @john", "Video1", true, "", ""),
+                ("John", "I saw example.com too", "Video1", false, "", ""),
+                ("Teah", "nice song", "Video1", false, "", "")];
+    let my_arr: Array2<ColumnVal> = rows_to_arr(&rows);
+    let links = link_functions::links_by_row(&my_arr);
+
+    let users = link_functions::user_link_stats(&my_arr, &links);
+    assert_eq!(2, users.len()); // Teah posted no links
+    assert_eq!(("Sara", 3, 1, vec!["example.com".to_string()]), (users[0].user.as_str(), users[0].links, users[0].handles, users[0].domains.clone()));
+
+    let domains = link_functions::domain_stats(&my_arr, &links);
+    assert_eq!(1, domains.len());
+    assert_eq!((3, 2, vec!["John".to_string(), "Sara".to_string()]), (domains[0].links, domains[0].spam_links, domains[0].users.clone()));
+
+    let graph = link_functions::user_domain_graph(&domains);
+    assert_eq!(vec!["domain:example.com".to_string()], graph["Sara"]);
+    assert_eq!(vec!["John".to_string(), "Sara".to_string()], graph["domain:example.com"]);
+}
+
+#[test]
+fn test_language_detection() {
+    assert_eq!(Some(language_functions::Script::Cyrillic), language_functions::dominant_script("Сергей Андреевич"));
+    assert_eq!(Some(language_functions::Script::Latin), language_functions::dominant_script("Ｈｅｌｌｏ 123"));
+    assert_eq!(None, language_functions::dominant_script("123 :) !!"));
+
+    assert_eq!("en", language_functions::detect_language("Please check out my new channel and subscribe"));
+    assert_eq!("es", language_functions::detect_language("Esta canción es la mejor de todas, me encanta"));
+    assert_eq!("pt", language_functions::detect_language("Eu amo essa música, é muito linda"));
+    assert_eq!("de", language_functions::detect_language("Ich liebe dieses Lied so sehr"));
+    assert_eq!("ru", language_functions::detect_language("Сергей Андреевич, подпишись на мой канал"));
+    assert_eq!("ja", language_functions::detect_language("この歌が大好きです"));
+    assert_eq!("ko", language_functions::detect_language("노래 좋아요"));
+    assert_eq!(language_functions::UNDETERMINED, language_functions::detect_language("wow"));
+    // links and handles are not profiled, and short English that sits about as close to another profile is undetermined
+    for spam in ["http://binbox.io/1FIRo#123", "https://www.facebook.com/teeLaLaLa", "Follow @teeLaLaLa https://www.facebook.com/teeLaLaLa",
+                 "PSY is a good guy", "Fantastic!", "I made a gaming channel"] {
+        assert!(["en", language_functions::UNDETERMINED].contains(&language_functions::detect_language(spam)), "{}", spam);
+    }
+
+    let giant_vec: Vec<ColumnVal> = vec![ColumnVal::One("Sara".to_string()), ColumnVal::One("Please check out my new channel".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("Sara".to_string()), ColumnVal::One("wow".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("Сергей".to_string()), ColumnVal::One("Подпишись на мой канал".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true)];
+    let my_arr: Array2<ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((3,4)).expect("Unable to reshape");
+    let with_language = language_functions::with_language_column(&my_arr);
+    assert_eq!(7, with_language.ncols()); // the missing date and comment id cols are padded
+    assert_eq!(None, time_functions::row_timestamp(&with_language, 0));
+    assert_eq!(vec![Some("en"), Some("und"), Some("ru")], (0..3).map(|i| language_functions::row_language(&with_language, i)).collect::<Vec<_>>());
+    assert_eq!(None, language_functions::row_language(&my_arr, 0));
+
+    let russian = language_functions::filter_languages(&with_language, &["ru"]);
+    assert_eq!(1, russian.nrows());
+    assert_eq!(Some("Сергей"), russian[[0, 0]].as_text());
+
+    let languages = language_functions::user_languages(&with_language);
+    assert_eq!("en", languages["Sara"]); // her undetermined "wow" doesn't count
+    let (my_map, users) = map_users_to_words(&with_language);
+    let graph = create_graph(&users, &my_map, 0.5);
+    let attrs = export_functions::node_attributes(&with_language, &graph, &users);
+    assert_eq!("ru", attrs["Сергей"].language);
+    let edges = export_functions::weighted_edges(&graph, &my_map);
+    assert!(export_functions::to_graphml(&graph, &attrs, &edges).contains("<data key=\"language\">ru</data>"));
+
+    // users of two different known languages are not linked; an undetermined user can be linked to anyone
+    let graph: HashMap<String, Vec<String>> = HashMap::from([("Sara".to_string(), vec!["Сергей".to_string(), "Jei".to_string()]),
+        ("Сергей".to_string(), vec!["Sara".to_string()]), ("Jei".to_string(), vec!["Sara".to_string()])]);
+    let same_language = language_functions::same_language_graph(&graph, &languages);
+    assert_eq!(vec!["Jei".to_string()], same_language["Sara"]); // Jei has no comments here, so is undetermined
+    assert!(same_language["Сергей"].is_empty());
+
+    // copies of a comment stay near-duplicates even when they were told apart as different languages
+    let mut copies = language_functions::with_language_column(&split_functions::select_rows(&my_arr, &[0, 0]));
+    copies[[1, language_functions::LANGUAGE_COL]] = ColumnVal::One("es".to_string());
+    assert_eq!(1, duplicate_functions::find_near_duplicates(&copies, 5, 20, 4, 0.8).len());
+}
+
+#[test]
+fn test_normalization() {
+    let all = normalize_functions::NormalizeOptions::all();
+    // full-width, accented, Cyrillic-homoglyph and leetspeak spellings all become "check"
+    for spelling in ["Ｃｈｅｃｋ", "chéck", "\u{0441}heck", "ch3ck", "CH3CK"] {
+        assert_eq!(vec!["check".to_string()], text_functions::tokenize(&normalize_functions::normalize(spelling, all)));
+    }
+    assert_eq!("freedom money paypal", normalize_functions::normalize("fr33dom m0ney p@ypal", all));
+    assert_eq!("wow!! in 2014", normalize_functions::normalize("wow!! in 2014", all)); // trailing symbols and numbers are kept
+    // only digits and symbols between letters are leetspeak; words starting with a digit, or ending in one, are real text
+    assert_eq!("100k views 1st mp3 4u fr33", normalize_functions::normalize("100k views 1st mp3 4u fr33", all));
+    assert_eq!("Сергей", normalize_functions::normalize("Сергей", all)); // real Cyrillic words are not folded
+
+    // each step only runs when it is turned on
+    let only_nfkc = normalize_functions::NormalizeOptions { nfkc: true, ..Default::default() };
+    assert_eq!("chéck", normalize_functions::normalize("ｃｈéｃｋ", only_nfkc));
+    assert_eq!("ch3ck", normalize_functions::normalize("ch3ck", normalize_functions::NormalizeOptions::default()));
+
+    let giant_vec: Vec<ColumnVal> = vec![ColumnVal::One("Sara".to_string()), ColumnVal::One("ch3ck my channel".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("John".to_string()), ColumnVal::One("Сheck my chаnnel".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true)];
+    let my_arr: Array2<ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((2,4)).expect("Unable to reshape");
+    let (raw_map, _) = map_users_to_words(&my_arr);
+    assert_eq!(Some(0.2), find_similarities("Sara".to_string(), "John".to_string(), &raw_map)); // only "my" is shared
+    let normalized_arr = normalize_functions::normalize_content(&my_arr, all);
+    let (normalized_map, users) = map_users_to_words(&normalized_arr);
+    assert_eq!(Some(1.0), find_similarities("Sara".to_string(), "John".to_string(), &normalized_map));
+
+    // what --normalize does in main: everything downstream of the word map sees the normalized content
+    assert!(create_graph(&users, &raw_map, 0.7)["Sara"].is_empty());
+    assert_eq!(vec!["John".to_string()], create_graph(&users, &normalized_map, 0.7)["Sara"]);
+    let model = bayes_functions::NaiveBayesModel::train(&normalized_arr, 1.0);
+    assert_eq!(Some(&2), model.spam_counts.get("check"));
+    assert!(!model.spam_counts.contains_key("ch3ck"));
+    // and a snapshot or model saved without it is stale with it
+    assert_ne!(csv_functions::data_hash(&my_arr), csv_functions::data_hash(&normalized_arr));
+}
+
+#[test]
+fn test_author_identity() {
+    let options = identity_functions::IdentityOptions { fold_case: true, collapse_whitespace: true };
+    assert_eq!("julius nm", identity_functions::identity_key(" Julius   NM ", options));
+    assert_eq!(" Julius   NM ", identity_functions::identity_key(" Julius   NM ", identity_functions::IdentityOptions { fold_case: false, collapse_whitespace: false }));
+    assert_eq!(3, identity_functions::edit_distance("kitten", "sitting"));
+    assert_eq!(0, identity_functions::edit_distance("Сергей", "Сергей"));
+
+    let path = std::env::temp_dir().join("test_author_aliases.tsv");
+    let path = path.to_str().unwrap();
+    std::fs::write(path, "# alias\tcanonical\nJ. NM\tJulius NM\nTeah99\tTeah\n").unwrap();
+    let aliases = identity_functions::load_aliases(path).unwrap();
+    assert_eq!(2, aliases.len());
+    std::fs::write(path, "no tab here\n").unwrap();
+    assert!(identity_functions::load_aliases(path).is_err());
+    std::fs::remove_file(path).unwrap();
+
+    let names = ["Julius NM", "julius nm ", "j. nm", "Teah99", "Teah", "Sara"];
+    let my_arr: Array2<ColumnVal> = rows_to_arr(&names.map(|name| (name, "check my channel", "Video1", name != "Sara", "", "")));
+
+    let identities = identity_functions::resolve_identities(&my_arr, options, &aliases);
+    assert_eq!("Julius NM", identities["julius nm "]);
+    assert_eq!("Julius NM", identities["j. nm"]); // the alias is matched by key too
+    assert_eq!("Teah", identities["Teah99"]);
+
+    // names typed by a person are resolved the same way, even spellings and aliases that aren't in the data
+    assert_eq!("Julius NM", identity_functions::resolve_name("JULIUS  nm", options, &aliases, &identities));
+    assert_eq!("Julius NM", identity_functions::resolve_name("J. nm", options, &aliases, &identities));
+    assert_eq!("Teah", identity_functions::resolve_name("teah99", options, &aliases, &identities));
+    assert_eq!("Nobody", identity_functions::resolve_name("Nobody", options, &aliases, &identities));
+
+    // the lowercase spelling comes first here, but the alias target still names the whole group
+    let first_lowercase = split_functions::select_rows(&my_arr, &[1, 0, 2]);
+    let identities_lowercase = identity_functions::resolve_identities(&first_lowercase, options, &aliases);
+    for name in ["julius nm ", "Julius NM", "j. nm"] {
+        assert_eq!("Julius NM", identities_lowercase[name]);
+    }
+
+    let canonical = identity_functions::canonicalize_authors(&my_arr, &identities);
+    let (my_map, users) = map_users_to_words(&canonical);
+    assert_eq!(3, users.len());
+    assert_eq!(3, my_map.len());
+    let (num_spam_users, _) = spam_functions::find_spam(&canonical, &users);
+    assert_eq!(2, num_spam_users);
+
+    let suggestions = identity_functions::merge_suggestions(&["Sara Lee".to_string(), "Sarah Lee".to_string(), "Bob".to_string(), "Rob".to_string()], options, 1, 4);
+    assert_eq!(vec![("Sara Lee".to_string(), "Sarah Lee".to_string(), 1)], suggestions); // Bob and Rob are too short to suggest
+}
+
+#[test]
+fn test_deduplication() {
+    let rows = [("Sara", "check my channel", "Video1", true, "", "c1"),
+                ("Sara", "check my channel", "Video1", true, "", "c2"),
+                ("Sara", "check my channel", "Video1", true, "", "c1"),
+                ("John", "check my channel", "Video1", true, "", "c3"),
+                ("Teah", "nice", "Video1", true, "", ""),
+                ("Teah", "great", "Video1", true, "", "")];
+    let my_arr: Array2<ColumnVal> = rows_to_arr(&rows);
+
+    let (by_id, report) = csv_functions::deduplicate(&my_arr, csv_functions::DedupKey::CommentId).unwrap();
+    assert_eq!((5, 1, vec![2]), (report.kept, report.dropped, report.dropped_rows.clone())); // rows without an id are kept
+    assert_eq!(5, by_id.nrows());
+
+    let (by_content, report) = csv_functions::deduplicate(&my_arr, csv_functions::DedupKey::AuthorContent).unwrap();
+    assert_eq!(vec![1, 2], report.dropped_rows); // John's copy of the text is a different author
+    assert_eq!(vec!["c1", "c3", "", ""], by_content.column(5).iter().map(|cell| cell.as_text().unwrap()).collect::<Vec<&str>>());
+
+    // per-user counts no longer see the repeats
+    let count_sara = |arr: &Array2<ColumnVal>| arr.column(0).iter().filter(|cell| cell.as_text() == Some("Sara")).count();
+    assert_eq!((3, 1), (count_sara(&my_arr), count_sara(&by_content)));
+
+    let without_ids = my_arr.slice(ndarray::s![.., 0..5]).to_owned();
+    assert!(csv_functions::deduplicate(&without_ids, csv_functions::DedupKey::CommentId).is_err());
+    assert!(csv_functions::deduplicate(&without_ids, csv_functions::DedupKey::AuthorContent).is_ok());
+
+    // the passes to run, as given with --dedup=
+    assert_eq!(Ok(vec![csv_functions::DedupKey::AuthorContent, csv_functions::DedupKey::CommentId]), csv_functions::parse_dedup_keys("author-content, id,id"));
+    assert_eq!(Ok(Vec::new()), csv_functions::parse_dedup_keys("none"));
+    assert!(csv_functions::parse_dedup_keys("id,none").is_err());
+    assert!(csv_functions::parse_dedup_keys("content").is_err());
+}
+
+#[test]
+fn test_label_audit() {
+    let rows = [("Sara", "Check out my channel please", "Video1", true, "", "c1"),
+                ("Sara", "nice song", "Video1", false, "", "c2"),
+                ("John", "Nice song!", "Video1", true, "", "c3"),
+                ("Teah", "I love this video so much", "Video1", false, "", "c4"),
+                ("Mike", "I love this video so much!!", "Video1", true, "", "c5"),
+                ("Anna", "check out my channel please", "Video1", true, "", "c6"),
+                ("Lia", "I love this video so much ok", "Video1", false, "", "c7")];
+    let my_arr: Array2<ColumnVal> = rows_to_arr(&rows);
+
+    let audit = audit_functions::audit_labels(&my_arr, 0.8);
+    assert_eq!(1, audit.mixed_authors.len());
+    assert_eq!(("Sara", vec![0], vec![1]), (audit.mixed_authors[0].key.as_str(), audit.mixed_authors[0].spam_rows.clone(), audit.mixed_authors[0].ham_rows.clone()));
+    // "nice song" and "i love this video so much" are each labeled both ways; the channel pitch is spam both times
+    assert_eq!(vec!["i love this video so much", "nice song"], audit.identical_texts.iter().map(|issue| issue.key.as_str()).collect::<Vec<&str>>());
+    assert!(audit.near_duplicates.iter().all(|issue| !issue.spam_rows.contains(&0)));
+    // rows 3 and 4 alone would repeat their identical-text issue; the cluster is only reported because Lia's text differs
+    assert_eq!(1, audit.near_duplicates.len());
+    assert_eq!((vec![4], vec![3, 6]), (audit.near_duplicates[0].spam_rows.clone(), audit.near_duplicates[0].ham_rows.clone()));
+    let without_lia = split_functions::select_rows(&my_arr, &[0, 1, 2, 3, 4, 5]);
+    assert!(audit_functions::audit_labels(&without_lia, 0.8).near_duplicates.is_empty());
+
+    let path = std::env::temp_dir().join("test_label_audit.csv");
+    let path = path.to_str().unwrap();
+    audit.write_report(path, &my_arr).expect("Unable to write the audit");
+    let text = std::fs::read_to_string(path).unwrap();
+    assert_eq!("issue,key,comment_id,author,video,label,content", text.lines().next().unwrap());
+    assert!(text.lines().nth(1).unwrap().starts_with("IdenticalText,i love this video so much,c5,Mike,Video1,spam,"));
+    assert!(text.lines().last().unwrap().starts_with("MixedAuthor,Sara,c2,Sara,Video1,ham,"));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_spammer_policy() {
+    let giant_vec: Vec<ColumnVal> = vec![ColumnVal::One("Sara".to_string()), ColumnVal::One("Pay me".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("John".to_string()), ColumnVal::One("Pay me".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("Maya".to_string()), ColumnVal::One("Subscribe to me".to_string()), ColumnVal::One("Video1".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("Sara".to_string()), ColumnVal::One("This is cool".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(false),
+                                        ColumnVal::One("Veri".to_string()), ColumnVal::One("Wow!".to_string()), ColumnVal::One("Video2".to_string()), ColumnVal::Two(false),
+                                        ColumnVal::One("Veri".to_string()), ColumnVal::One("Pay me".to_string()), ColumnVal::One("Video3".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("Maya".to_string()), ColumnVal::One("Check my channel".to_string()), ColumnVal::One("Video3".to_string()), ColumnVal::Two(true),
+                                        ColumnVal::One("Teah".to_string()), ColumnVal::One("I love this video".to_string()), ColumnVal::One("Video3".to_string()), ColumnVal::Two(false)];
+    let my_arr: Array2<ColumnVal> = Array::from_vec(giant_vec).into_shape_with_order((8,4)).expect("Unable to reshape");
+    let (_, users) = map_users_to_words(&my_arr);
+    let names = |list: &[&str]| -> Vec<String> { list.iter().map(|name| name.to_string()).collect() };
+
+    // Sara and Veri have 1 spam comment of 2, John 1 of 1, Maya 2 of 2, Teah none
+    assert_eq!((4, names(&["Sara", "John", "Maya", "Veri"])), spam_functions::find_spam_with_policy(&my_arr, spam_functions::SpammerPolicy::AnySpam));
+    assert_eq!(spam_functions::find_spam(&my_arr, &users), spam_functions::find_spam_with_policy(&my_arr, spam_functions::SpammerPolicy::AnySpam));
+    assert_eq!((2, names(&["John", "Maya"])), spam_functions::find_spam_with_policy(&my_arr, spam_functions::SpammerPolicy::MajoritySpam));
+    assert_eq!((2, names(&["John", "Maya"])), spam_functions::find_spam_with_policy(&my_arr, spam_functions::SpammerPolicy::AllSpam));
+    assert_eq!((4, names(&["Sara", "John", "Maya", "Veri"])), spam_functions::find_spam_with_policy(&my_arr, spam_functions::SpammerPolicy::RatioAtLeast(0.5)));
+    assert_eq!((2, names(&["John", "Maya"])), spam_functions::find_spam_with_policy(&my_arr, spam_functions::SpammerPolicy::RatioAtLeast(0.75)));
+    assert_eq!((1, names(&["Maya"])), spam_functions::find_spam_with_policy(&my_arr, spam_functions::SpammerPolicy::AtLeastK(2)));
+    assert!(!spam_functions::SpammerPolicy::AtLeastK(0).is_spammer(3, 0)); // no spam comment, never a spammer
+
+    // the spam-only subset keeps only the spam comments of the policy's spammers
+    let (_, majority) = spam_functions::find_spam_with_policy(&my_arr, spam_functions::SpammerPolicy::MajoritySpam);
+    let subset = spam_functions::spam_subset(&my_arr, &majority);
+    let subset_rows: Vec<(String, String)> = subset.rows().into_iter()
+        .map(|row| (row[0].as_text().unwrap_or("").to_string(), row[1].as_text().unwrap_or("").to_string()))
+        .collect();
+    assert_eq!(vec![("John".to_string(), "Pay me".to_string()), ("Maya".to_string(), "Subscribe to me".to_string()), ("Maya".to_string(), "Check my channel".to_string())], subset_rows);
+
+    // and quick_stats counts the spammers under the dataframe's policy
+    let mut df = DataFrame::new(my_arr, users.len() as u32, 4);
+    df.set_spammer_policy(spam_functions::SpammerPolicy::AllSpam);
+    assert_eq!(2, df.total_spam_users);
+    assert_eq!(spam_functions::SpammerPolicy::AllSpam, df.group_by(GroupKey::Column(2), &[Aggregation::Count]).spammer_policy);
+}
+
+#[test]
+fn test_spam_summary() {
+    let rows = [("Sara", "Pay me", "Video1", false, "2014-01-19T10:00:00", ""),
+                ("John", "Subscribe", "Video1", true, "2014-01-20T08:00:00", ""),
+                ("Sara", "Check my channel", "Video1", true, "2014-01-18T09:30:00", ""),
+                ("Veri", "Wow!", "Video1", false, "", ""),
+                ("John", "Subscribe again", "Video1", true, "2014-01-21T12:00:00", ""),
+                ("Maya", "Pay me", "Video1", true, "2014-01-22T00:00:00", "")];
+    let my_arr: Array2<ColumnVal> = rows_to_arr(&rows);
+
+    let summaries = spam_functions::spam_summary(&my_arr);
+    assert_eq!(vec!["Sara", "John", "Veri", "Maya"], summaries.iter().map(|s| s.user.as_str()).collect::<Vec<&str>>()); // first comment order
+    let sara = &summaries[0];
+    assert_eq!((2, 1, 0, 2, Some(2)), (sara.comments, sara.spam_comments, sara.first_row, sara.last_row, sara.first_spam_row));
+    // seen by date, not by row: her later row is the earlier comment
+    assert_eq!(Some("2014-01-18T09:30:00".to_string()), sara.first_seen.map(time_functions::format_timestamp));
+    assert_eq!(Some("2014-01-19T10:00:00".to_string()), sara.last_seen.map(time_functions::format_timestamp));
+    assert_eq!((None, None, None), (summaries[2].first_spam_row, summaries[2].first_seen, summaries[2].last_seen));
+
+    // spammers come in the order of their first spam comment, so John is before Sara
+    let (_, users) = map_users_to_words(&my_arr);
+    assert_eq!((3, vec!["John".to_string(), "Sara".to_string(), "Maya".to_string()]), spam_functions::find_spam(&my_arr, &users));
+}
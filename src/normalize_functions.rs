@@ -0,0 +1,164 @@
+use ndarray::Array2;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+// Module summary: an optional normalization stage that runs on comment content before tokenization, so the
+// obfuscated spellings spammers use to dodge filters ("Ｃｈｅｃｋ", "chéck", "сheck" with a Cyrillic с, "ch3ck")
+// all become the same token. Each step can be turned on or off on its own.
+
+
+// which normalization steps to run. They run in the order of the fields
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NormalizeOptions {
+    pub nfkc: bool, // Unicode NFKC: full-width letters, ligatures, circled and styled letters become plain ones
+    pub fold_confusables: bool, // Cyrillic and Greek letters that look like Latin ones become the Latin letter
+    pub strip_diacritics: bool, // é -> e, ñ -> n, ü -> u (Latin letters only)
+    pub map_leetspeak: bool, // digits and symbols standing in for letters inside words: ch3ck -> check, fr33dom -> freedom
+}
+
+impl NormalizeOptions {
+    // every step turned on
+    pub fn all() -> NormalizeOptions {
+        NormalizeOptions { nfkc: true, fold_confusables: true, strip_diacritics: true, map_leetspeak: true }
+    }
+}
+
+
+// purpose: find the Latin letter a Cyrillic or Greek letter is a lookalike of
+// input: a character
+// output: Some(the Latin letter), or None if it isn't a known lookalike
+// the table is a small hand-picked part of the Unicode confusables list (UTS #39), limited to letters that are
+// near-identical in common fonts
+fn latin_lookalike(c: char) -> Option<char> {
+    let latin = match c {
+        'а' | 'α' => 'a', 'А' | 'Α' => 'A',
+        'В' | 'Β' => 'B', 'ь' => 'b',
+        'с' | 'ϲ' => 'c', 'С' | 'Ϲ' => 'C',
+        'ԁ' => 'd',
+        'е' | 'ε' => 'e', 'Е' | 'Ε' => 'E',
+        'Н' | 'Η' => 'H', 'һ' => 'h',
+        'і' | 'ι' => 'i', 'І' | 'Ι' => 'I',
+        'ј' => 'j', 'Ј' => 'J',
+        'κ' => 'k', 'К' | 'Κ' => 'K',
+        'М' | 'Μ' => 'M',
+        'Ν' => 'N', 'η' => 'n',
+        'о' | 'ο' => 'o', 'О' | 'Ο' => 'O',
+        'р' | 'ρ' => 'p', 'Р' | 'Ρ' => 'P',
+        'ԛ' => 'q',
+        'ѕ' => 's', 'Ѕ' => 'S',
+        'Т' | 'Τ' => 'T', 'τ' => 't',
+        'υ' => 'u',
+        'ν' => 'v',
+        'ԝ' => 'w',
+        'х' | 'χ' => 'x', 'Х' | 'Χ' => 'X',
+        'у' => 'y', 'У' | 'Υ' => 'Y',
+        'Ζ' => 'Z',
+        _ => return None,
+    };
+    Some(latin)
+}
+
+
+// the letter a leetspeak character stands for
+fn leet_letter(c: char) -> Option<char> {
+    let letter = match c {
+        '0' => 'o',
+        '1' | '!' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        '8' => 'b',
+        _ => return None,
+    };
+    Some(letter)
+}
+
+
+// purpose: fold the lookalike letters of one word
+// input: a word
+// output: the word with every lookalike replaced by its Latin letter, when the word mixes in Latin letters or all its
+// letters are lookalikes. Other words are real Cyrillic or Greek and are left alone
+fn fold_word(word: &str) -> String {
+    let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    let has_latin = letters.iter().any(|c| c.is_ascii_alphabetic());
+    let all_lookalikes = !letters.is_empty() && letters.iter().all(|c| c.is_ascii_alphabetic() || latin_lookalike(*c).is_some());
+    if !(has_latin || all_lookalikes) {
+        return word.to_string();
+    }
+    word.chars().map(|c| latin_lookalike(c).unwrap_or(c)).collect()
+}
+
+
+// purpose: undo the leetspeak of one word
+// input: a word
+// output: the word with the leetspeak characters that sit between letters replaced by letters: "ch3ck" -> "check",
+// "fr33dom" -> "freedom", "p@y" -> "pay". A run of digits or symbols only counts when letters are on both sides of
+// it, and words starting with a digit are left alone, so "100k", "1st", "mp3", "4u", "2014" and "wow!!" keep their
+// digits and symbols
+fn unleet_word(word: &str) -> String {
+    let chars: Vec<char> = word.chars().collect();
+    if chars.first().is_none_or(|c| c.is_ascii_digit()) {
+        return word.to_string();
+    }
+    let mut out = chars.clone();
+    let mut start = 1;
+    while start < chars.len() {
+        if !chars[start - 1].is_alphabetic() || leet_letter(chars[start]).is_none() {
+            start += 1;
+            continue;
+        }
+        let end = (start..chars.len()).find(|i| leet_letter(chars[*i]).is_none()).unwrap_or(chars.len());
+        if end < chars.len() && chars[end].is_alphabetic() {
+            for (slot, c) in out[start..end].iter_mut().zip(chars[start..end].iter()) {
+                *slot = leet_letter(*c).unwrap_or(*c);
+            }
+        }
+        start = end + 1;
+    }
+    out.into_iter().collect()
+}
+
+
+// purpose: take the accent off a Latin letter
+// input: a (composed) character
+// output: its base letter when it is a Latin letter with diacritics (é -> e), the character itself otherwise. Letters
+// of other scripts keep their marks, since there they make a different letter (Cyrillic й is not и)
+fn strip_diacritic(c: char) -> char {
+    let mut decomposed = std::iter::once(c).nfd();
+    match decomposed.next() {
+        Some(base) if base.is_ascii_alphabetic() && decomposed.all(is_combining_mark) => base,
+        _ => c,
+    }
+}
+
+
+// purpose: normalize a comment
+// input: the comment content and the steps to run
+// output: the normalized content. Words stay separated by the same spaces, so tokenize() splits it the same way
+pub fn normalize(content: &str, options: NormalizeOptions) -> String {
+    let mut text: String = if options.nfkc { content.nfkc().collect() } else { content.to_string() };
+    if options.fold_confusables {
+        text = text.split(' ').map(fold_word).collect::<Vec<String>>().join(" ");
+    }
+    if options.strip_diacritics {
+        text = text.nfc().map(strip_diacritic).collect();
+    }
+    if options.map_leetspeak {
+        text = text.split(' ').map(unleet_word).collect::<Vec<String>>().join(" ");
+    }
+    text
+}
+
+
+// purpose: run the normalization stage on the whole data
+// input: the array of data (col 1 holds the content) and the steps to run
+// output: a copy of the array whose col 1 is normalized, ready for map_users_to_words() and every other tokenizer
+pub fn normalize_content(arr_data: &Array2<crate::ColumnVal>, options: NormalizeOptions) -> Array2<crate::ColumnVal> {
+    let mut out = arr_data.clone();
+    for i in 0..out.nrows() {
+        let normalized = normalize(out[[i, 1]].as_text().unwrap_or(""), options);
+        out[[i, 1]] = crate::ColumnVal::One(normalized);
+    }
+    out
+}
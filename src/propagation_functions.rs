@@ -0,0 +1,105 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+
+// Module summary: semi-supervised label propagation on the user similarity graph. Users with a known label (from
+// find_spam) keep it, and every other user's spam score becomes the average of its neighbors' scores.
+
+
+// purpose: build the label of every user from the output of find_spam
+// input: a vector of unique users and a vector of the spammers among them
+// output: a hashmap mapping each user to true (spammer) or false (not a spammer)
+pub fn user_labels(users: &[String], spammers: &[String]) -> HashMap<String, bool> {
+    let mut labels: HashMap<String, bool> = HashMap::new();
+    for user in users.iter() {
+        labels.insert(user.clone(), false);
+    }
+    for spammer in spammers.iter() {
+        labels.insert(spammer.clone(), true);
+    }
+    labels
+}
+
+
+// purpose: infer a spam score for every unlabeled user of the graph
+// input: a graph mapping users to their neighbors, the known labels (seeds), the max # of iterations and the tolerance
+// output: a hashmap mapping every user of the graph to a spam score between 0 and 1. Seeds keep 1.0 or 0.0
+// starts every unlabeled user at the seeds' spam rate, then repeatedly replaces each unlabeled user's score by the mean
+// score of its neighbors, until no score moves by more than the tolerance. Users with no path to a seed keep the
+// starting score. Users are updated in sorted order from the previous iteration's scores, so results are deterministic
+pub fn propagate_labels(graph: &HashMap<String, Vec<String>>, seeds: &HashMap<String, bool>, max_iters: u32, tolerance: f64) -> HashMap<String, f64> {
+    let num_seed_spam = seeds.values().filter(|is_spam| **is_spam).count();
+    let prior = if seeds.is_empty() { 0.5 } else { num_seed_spam as f64 / seeds.len() as f64 };
+
+    let mut scores: HashMap<String, f64> = HashMap::new();
+    for user in graph.keys() {
+        let score = match seeds.get(user) {
+            Some(true) => 1.0,
+            Some(false) => 0.0,
+            None => prior,
+        };
+        scores.insert(user.clone(), score);
+    }
+
+    let mut unlabeled: Vec<&String> = graph.keys().filter(|user| !seeds.contains_key(*user)).collect();
+    unlabeled.sort();
+
+    for _ in 0..max_iters {
+        let mut new_scores = scores.clone();
+        let mut max_change: f64 = 0.0;
+        for user in unlabeled.iter() {
+            let neighbors = &graph[*user];
+            if neighbors.is_empty() {
+                continue;
+            }
+            let mean = neighbors.iter().map(|n| scores.get(n).copied().unwrap_or(prior)).sum::<f64>() / neighbors.len() as f64;
+            max_change = max_change.max((mean - scores[*user]).abs());
+            new_scores.insert(user.to_string(), mean);
+        }
+        scores = new_scores;
+        if max_change < tolerance {
+            break;
+        }
+    }
+    scores
+}
+
+
+// It is the result of hiding some labels and trying to recover them with propagate_labels()
+#[derive(Debug, Clone)]
+pub struct HoldoutResult {
+    pub report: crate::eval_functions::EvalReport,
+    pub num_hidden: usize,
+    pub num_hidden_with_neighbors: usize, // hidden users that have at least one neighbor, i.e. that the graph could say anything about
+}
+
+
+// purpose: measure how well graph proximity alone predicts spamminess
+// input: a graph, the labels of its users, the fraction of users whose label is hidden, and a seed for choosing them
+// output: a HoldoutResult scoring the propagated scores of the hidden users against their real labels
+// shuffles the labeled users (sorted first so the seed alone decides the order), hides holdout_fraction of them,
+// propagates from the rest and evaluates the hidden users with a 0.5 threshold
+pub fn holdout_propagation(graph: &HashMap<String, Vec<String>>, labels: &HashMap<String, bool>, holdout_fraction: f64, seed: u64) -> HoldoutResult {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut labeled_users: Vec<&String> = labels.keys().filter(|user| graph.contains_key(*user)).collect();
+    labeled_users.sort();
+    labeled_users.shuffle(&mut rng);
+
+    let num_hidden = (labeled_users.len() as f64 * holdout_fraction).round() as usize;
+    let hidden: Vec<&String> = labeled_users[..num_hidden].to_vec();
+    let mut seeds = labels.clone();
+    for user in hidden.iter() {
+        seeds.remove(*user);
+    }
+
+    let scores = propagate_labels(graph, &seeds, 100, 1e-6);
+    let hidden_scores: Vec<f64> = hidden.iter().map(|user| scores[*user]).collect();
+    let hidden_labels: Vec<bool> = hidden.iter().map(|user| labels[*user]).collect();
+
+    HoldoutResult {
+        report: crate::eval_functions::evaluate_scores(&hidden_scores, &hidden_labels, 0.5, 10),
+        num_hidden,
+        num_hidden_with_neighbors: hidden.iter().filter(|user| !graph[**user].is_empty()).count(),
+    }
+}
@@ -0,0 +1,184 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+
+// Module summary: saving and loading a snapshot of the expensive part of the analysis (the user -> words map, the user
+// list, the similarity graph and the spam labels) to a versioned binary file, so repeated runs don't have to
+// re-tokenize and rebuild the O(n^2) graph. Snapshots written by another format version are refused, and the hash of
+// the data they were built from tells when they are out of date.
+
+// the first bytes of every snapshot file
+const MAGIC: &[u8; 8] = b"YTSPAMSN";
+
+// bump this whenever the layout written by save() changes, so old files are refused instead of misread
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+
+// It holds everything needed to skip map_users_to_words(), find_spam() and create_graph() on the next run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub source_rows: u64, // # of rows of the data the snapshot was built from
+    pub data_hash: u64, // csv_functions::data_hash() of that data
+    pub threshold: f64, // similarity threshold the graph was built with
+    pub users: Vec<String>,
+    pub user_words: HashMap<String, HashSet<String>>,
+    pub graph: HashMap<String, Vec<String>>,
+    pub spammers: Vec<String>,
+}
+
+
+// little-endian writers for the few types the format needs. Strings are a u32 byte length followed by UTF-8 bytes
+fn write_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_u64(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_f64(out: &mut Vec<u8>, value: f64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_str(out: &mut Vec<u8>, value: &str) {
+    write_u32(out, value.len() as u32);
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn write_str_list<S: AsRef<str>>(out: &mut Vec<u8>, values: &[S]) {
+    write_u32(out, values.len() as u32);
+    for value in values.iter() {
+        write_str(out, value.as_ref());
+    }
+}
+
+
+// It reads the values back in the order they were written, failing (instead of panicking) on a truncated file
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.pos + len > self.bytes.len() {
+            return Err(format!("snapshot is truncated at byte {}", self.pos));
+        }
+        let slice = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn read_f64(&mut self) -> Result<f64, String> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn read_str(&mut self) -> Result<String, String> {
+        let len = self.read_u32()? as usize;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| format!("snapshot has invalid UTF-8 before byte {}", self.pos))
+    }
+
+    fn read_str_list(&mut self) -> Result<Vec<String>, String> {
+        let len = self.read_u32()?;
+        (0..len).map(|_| self.read_str()).collect()
+    }
+}
+
+
+impl Snapshot {
+
+    // purpose: write the snapshot to a binary file
+    // input: the path to write
+    // output: an io Result
+    // writes the magic bytes, the format version, the three numbers, the user list, then the word map and the graph as
+    // (user, list) pairs sorted by user (words sorted too), and finally the spammers. Sorting makes the same
+    // analysis always produce the same bytes
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let mut out: Vec<u8> = Vec::new();
+        out.extend_from_slice(MAGIC);
+        write_u32(&mut out, SNAPSHOT_VERSION);
+        write_u64(&mut out, self.source_rows);
+        write_u64(&mut out, self.data_hash);
+        write_f64(&mut out, self.threshold);
+        write_str_list(&mut out, &self.users);
+
+        let mut word_users: Vec<&String> = self.user_words.keys().collect();
+        word_users.sort();
+        write_u32(&mut out, word_users.len() as u32);
+        for user in word_users {
+            let mut words: Vec<&String> = self.user_words[user].iter().collect();
+            words.sort();
+            write_str(&mut out, user);
+            write_str_list(&mut out, &words);
+        }
+
+        let mut graph_users: Vec<&String> = self.graph.keys().collect();
+        graph_users.sort();
+        write_u32(&mut out, graph_users.len() as u32);
+        for user in graph_users {
+            write_str(&mut out, user);
+            write_str_list(&mut out, &self.graph[user]);
+        }
+
+        write_str_list(&mut out, &self.spammers);
+        fs::write(path, out)
+    }
+
+    // purpose: read a snapshot written by save()
+    // input: the path of the snapshot file
+    // output: Ok(the snapshot) or Err(why it can't be used): missing file, not a snapshot, another format version, or a damaged file
+    pub fn load(path: &str) -> Result<Snapshot, String> {
+        let bytes = fs::read(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+        let mut rdr = ByteReader { bytes: &bytes, pos: 0 };
+
+        if rdr.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err(format!("{} is not an analysis snapshot", path));
+        }
+        let version = rdr.read_u32()?;
+        if version != SNAPSHOT_VERSION {
+            return Err(format!("{} has snapshot format version {}, but this program reads version {}", path, version, SNAPSHOT_VERSION));
+        }
+
+        let source_rows = rdr.read_u64()?;
+        let data_hash = rdr.read_u64()?;
+        let threshold = rdr.read_f64()?;
+        let users = rdr.read_str_list()?;
+
+        let mut user_words: HashMap<String, HashSet<String>> = HashMap::new();
+        for _ in 0..rdr.read_u32()? {
+            let user = rdr.read_str()?;
+            user_words.insert(user, rdr.read_str_list()?.into_iter().collect());
+        }
+
+        let mut graph: HashMap<String, Vec<String>> = HashMap::new();
+        for _ in 0..rdr.read_u32()? {
+            let user = rdr.read_str()?;
+            graph.insert(user, rdr.read_str_list()?);
+        }
+
+        let spammers = rdr.read_str_list()?;
+        if rdr.pos != bytes.len() {
+            return Err(format!("{} has {} unexpected trailing bytes", path, bytes.len() - rdr.pos));
+        }
+
+        Ok(Snapshot { source_rows, data_hash, threshold, users, user_words, graph, spammers })
+    }
+
+    // purpose: tell whether the snapshot is still up to date
+    // input: the array of data about to be analyzed and the similarity threshold about to be used
+    // output: true if the snapshot was built from the same rows (same count and same data_hash) with the same threshold,
+    // so editing one comment, relabeling one or renaming an author makes it stale
+    pub fn matches(&self, arr_data: &Array2<crate::ColumnVal>, threshold: f64) -> bool {
+        self.threshold == threshold && self.source_rows == arr_data.nrows() as u64 && self.data_hash == crate::csv_functions::data_hash(arr_data)
+    }
+}
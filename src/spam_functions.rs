@@ -10,33 +10,119 @@ use std::collections::VecDeque;
 
 
 
+// which users count as spammers. Every policy needs at least one comment labeled spam, so a user without any is never a spammer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpammerPolicy {
+    AnySpam, // at least one spam comment (the original definition)
+    MajoritySpam, // more than half of the user's comments are spam
+    AllSpam, // every comment of the user is spam
+    RatioAtLeast(f64), // the share of spam comments is at least p (0.0 - 1.0)
+    AtLeastK(u32), // at least k spam comments
+}
+
+impl SpammerPolicy {
+    // purpose: decide whether a user is a spammer
+    // input: the user's number of comments and number of spam comments
+    // output: true if the user counts as a spammer under this policy
+    pub fn is_spammer(&self, comments: u32, spam_comments: u32) -> bool {
+        if spam_comments == 0 {
+            return false;
+        }
+        match *self {
+            SpammerPolicy::AnySpam => true,
+            SpammerPolicy::MajoritySpam => 2 * spam_comments > comments,
+            SpammerPolicy::AllSpam => spam_comments == comments,
+            SpammerPolicy::RatioAtLeast(p) => spam_comments as f64 >= p * comments as f64,
+            SpammerPolicy::AtLeastK(k) => spam_comments >= k,
+        }
+    }
+}
+
+
+// It is what one user posted: how much, how much of it is spam, and when
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserSpamSummary {
+    pub user: String,
+    pub comments: u32,
+    pub spam_comments: u32,
+    pub first_row: usize, // the row of the user's first comment
+    pub last_row: usize, // the row of the user's last comment
+    pub first_spam_row: Option<usize>, // the row of the user's first spam comment, None if there is none
+    pub first_seen: Option<i64>, // the timestamp of the user's earliest dated comment, None if no comment has a date
+    pub last_seen: Option<i64>, // the timestamp of the user's latest dated comment
+}
+
+
+// purpose: summarize the comments of every user
+// input: the array of data (cols 0-user, 3-class, and 4-date when there is one)
+// output: a vector with one UserSpamSummary per user, in the order of the users' first comments
+// one pass over the rows: a hashmap from user to their position in the vector finds each user's summary, so this is
+// linear in the number of comments
+pub fn spam_summary(arr_data: &Array2<crate::ColumnVal>) -> Vec<UserSpamSummary> {
+    let mut position: HashMap<String, usize> = HashMap::new();
+    let mut summaries: Vec<UserSpamSummary> = Vec::new();
+    for i in 0..arr_data.nrows() {
+        let user = arr_data[[i, 0]].as_text().unwrap_or("");
+        let index = match position.get(user) {
+            Some(index) => *index,
+            None => {
+                position.insert(user.to_string(), summaries.len());
+                summaries.push(UserSpamSummary { user: user.to_string(), comments: 0, spam_comments: 0, first_row: i, last_row: i,
+                                                 first_spam_row: None, first_seen: None, last_seen: None });
+                summaries.len() - 1
+            },
+        };
+        let summary = &mut summaries[index];
+        summary.comments += 1;
+        summary.last_row = i;
+        if arr_data[[i, 3]].as_bool() == Some(true) { // col 3 is the classification col
+            summary.spam_comments += 1;
+            summary.first_spam_row.get_or_insert(i);
+        }
+        if let Some(timestamp) = crate::time_functions::row_timestamp(arr_data, i) {
+            summary.first_seen = Some(summary.first_seen.map_or(timestamp, |seen| seen.min(timestamp)));
+            summary.last_seen = Some(summary.last_seen.map_or(timestamp, |seen| seen.max(timestamp)));
+        }
+    }
+    summaries
+}
+
+
 // purpose: find information about the data specifically marked as spam
-// input: the array of data and a vector of unique users
+// input: the array of data and a vector of unique users (the users come from the data itself, so it isn't read)
 // output: the number of unique spammers and a vector of those unique spam users' names
-// it iterates over the column of the array that corresponds to the classification as spam or not
-pub fn find_spam(arr_data: &Array2<crate::ColumnVal>, unique_users: &Vec<String>) -> (u32, Vec<String>) { // returns number of spam users and a vector of spam users
-    // We know the spam column is in the index col 3 of arr_data, a col of bools
-    // A user will be considered a spammer if at least ONE of their comments is marked as spam
-
-    let mut seen_before: Vec<String> = Vec::new();
-    let mut spam_users: Vec<String> = Vec::new();
-    let mut counter: u32 = 0;
-    for (num, item) in arr_data.column(3).iter().enumerate() { // col 3 is the classification col
-        let my_bool: bool = match item { // unpack the ColumnVal with a match statement
-            crate::ColumnVal::One(_) => false,
-            crate::ColumnVal::Two(some_bool) => *some_bool,
-        };
-        let given_name: String = match &arr_data[[num, 0]] { // similar unpacking here
-            crate::ColumnVal::One(name) => name.to_string(),
-            crate::ColumnVal::Two(some_bool) => format!("Dummy name"), // should not execute
-        };
-        if my_bool && seen_before.contains(&given_name) == false { // then we have found a new unique user
-            counter += 1;
-            spam_users.push(given_name.to_string());
-            seen_before.push(given_name.to_string());
+// a user is a spammer if at least ONE of their comments is marked as spam (SpammerPolicy::AnySpam)
+pub fn find_spam(arr_data: &Array2<crate::ColumnVal>, _unique_users: &Vec<String>) -> (u32, Vec<String>) { // returns number of spam users and a vector of spam users
+    find_spam_with_policy(arr_data, SpammerPolicy::AnySpam)
+}
+
+
+// purpose: find the spammers under a given definition of spammer
+// input: the array of data and the policy deciding who is a spammer
+// output: the number of unique spammers and a vector of their names, in the order of their first spam comment
+// the spammers are placed in a vector indexed by the row of their first spam comment, so putting them in order is
+// linear too
+pub fn find_spam_with_policy(arr_data: &Array2<crate::ColumnVal>, policy: SpammerPolicy) -> (u32, Vec<String>) {
+    let mut by_first_spam: Vec<Option<String>> = vec![None; arr_data.nrows()];
+    for summary in spam_summary(arr_data).into_iter().filter(|s| policy.is_spammer(s.comments, s.spam_comments)) {
+        if let Some(row) = summary.first_spam_row {
+            by_first_spam[row] = Some(summary.user);
         }
     }
-    return (counter, spam_users);
+    let spam_users: Vec<String> = by_first_spam.into_iter().flatten().collect();
+    (spam_users.len() as u32, spam_users)
+}
+
+
+// purpose: build the spam-only subset of the data under a policy
+// input: the array of data and the spammers from find_spam_with_policy()
+// output: a new array with only the spam comments of those spammers, in the same order
+pub fn spam_subset(arr_data: &Array2<crate::ColumnVal>, spammers: &[String]) -> Array2<crate::ColumnVal> {
+    let spammers: HashSet<&str> = spammers.iter().map(|name| name.as_str()).collect();
+    let rows: Vec<usize> = (0..arr_data.nrows())
+        .filter(|i| arr_data[[*i, 3]].as_bool() == Some(true) && arr_data[[*i, 0]].as_text().is_some_and(|name| spammers.contains(name)))
+        .collect();
+    crate::split_functions::select_rows(arr_data, &rows)
 }
 
 
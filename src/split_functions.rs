@@ -0,0 +1,138 @@
+use ndarray::{Array2, Axis};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+
+// Module summary: seeded train/test splitting and stratified k-fold cross-validation over the comment table.
+// Rows can be grouped by author or video so that the same user (or video) never ends up on both sides of a split.
+
+
+// what a single unit of the split is. All rows of one group always go to the same side
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    Comment, // every row is its own group
+    Author, // col 0
+    Video, // col 2
+}
+
+
+// purpose: collect the row indices of each group, split into spam groups and ham groups
+// input: the array of data and what to group by
+// output: (spam groups, ham groups), each a vector of groups, each group a vector of row indices
+// a group is counted as spam if any of its rows is labeled spam (col 3). Groups keep their first-appearance order,
+// so the result only depends on the data and not on hashing
+fn stratified_groups(arr_data: &Array2<crate::ColumnVal>, group_by: GroupBy) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+    let mut group_index: HashMap<String, usize> = HashMap::new();
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_is_spam: Vec<bool> = Vec::new();
+
+    for (i, row) in arr_data.rows().into_iter().enumerate() {
+        let key = match group_by {
+            GroupBy::Comment => i.to_string(),
+            GroupBy::Author => row[0].as_text().unwrap_or("").to_string(),
+            GroupBy::Video => row[2].as_text().unwrap_or("").to_string(),
+        };
+        let g = *group_index.entry(key).or_insert_with(|| {
+            groups.push(Vec::new());
+            group_is_spam.push(false);
+            groups.len() - 1
+        });
+        groups[g].push(i);
+        if row[3].as_bool().unwrap_or(false) {
+            group_is_spam[g] = true;
+        }
+    }
+
+    let mut spam_groups: Vec<Vec<usize>> = Vec::new();
+    let mut ham_groups: Vec<Vec<usize>> = Vec::new();
+    for (group, is_spam) in groups.into_iter().zip(group_is_spam) {
+        if is_spam {
+            spam_groups.push(group);
+        } else {
+            ham_groups.push(group);
+        }
+    }
+    (spam_groups, ham_groups)
+}
+
+
+// purpose: make a new array out of some rows of an existing one
+// input: the array of data and the row indices to keep
+// output: a new array with those rows, in the given order
+pub fn select_rows(arr_data: &Array2<crate::ColumnVal>, rows: &[usize]) -> Array2<crate::ColumnVal> {
+    arr_data.select(Axis(0), rows)
+}
+
+
+// purpose: split the data into a training set and a test set
+// input: the array of data, the fraction of groups to put in the test set, a seed for the shuffle, and what to group by
+// output: (train array, test array)
+// shuffles the spam groups and the ham groups separately with the seed and moves test_fraction of each into the test
+// set, so both sets keep roughly the same spam rate. The same seed always gives the same split
+pub fn train_test_split(arr_data: &Array2<crate::ColumnVal>, test_fraction: f64, seed: u64, group_by: GroupBy) -> (Array2<crate::ColumnVal>, Array2<crate::ColumnVal>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (mut spam_groups, mut ham_groups) = stratified_groups(arr_data, group_by);
+
+    let mut train_rows: Vec<usize> = Vec::new();
+    let mut test_rows: Vec<usize> = Vec::new();
+    for stratum in [&mut spam_groups, &mut ham_groups] {
+        stratum.shuffle(&mut rng);
+        let num_test = (stratum.len() as f64 * test_fraction).round() as usize;
+        for (num, group) in stratum.iter().enumerate() {
+            if num < num_test {
+                test_rows.extend(group);
+            } else {
+                train_rows.extend(group);
+            }
+        }
+    }
+    train_rows.sort();
+    test_rows.sort();
+    (select_rows(arr_data, &train_rows), select_rows(arr_data, &test_rows))
+}
+
+
+// purpose: build the folds for stratified k-fold cross-validation
+// input: the array of data, the # of folds, a seed for the shuffle, and what to group by
+// output: a vector of k (train array, test array) pairs. Every row is in exactly one test array
+// shuffles the spam groups and ham groups separately, then deals each stratum out over the folds, always giving the next
+// group to the fold with the fewest rows so far. That keeps the folds similar in size and in spam rate
+pub fn stratified_k_fold(arr_data: &Array2<crate::ColumnVal>, k: usize, seed: u64, group_by: GroupBy) -> Vec<(Array2<crate::ColumnVal>, Array2<crate::ColumnVal>)> {
+    let k = k.max(2);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (mut spam_groups, mut ham_groups) = stratified_groups(arr_data, group_by);
+
+    let mut fold_rows: Vec<Vec<usize>> = vec![Vec::new(); k];
+    for stratum in [&mut spam_groups, &mut ham_groups] {
+        stratum.shuffle(&mut rng);
+        for group in stratum.iter() {
+            let smallest = (0..k).min_by_key(|f| fold_rows[*f].len()).unwrap();
+            fold_rows[smallest].extend(group);
+        }
+    }
+
+    let mut folds = Vec::new();
+    for f in 0..k {
+        let mut test_rows = fold_rows[f].clone();
+        let mut train_rows: Vec<usize> = (0..k).filter(|other| *other != f).flat_map(|other| fold_rows[other].clone()).collect();
+        test_rows.sort();
+        train_rows.sort();
+        folds.push((select_rows(arr_data, &train_rows), select_rows(arr_data, &test_rows)));
+    }
+    folds
+}
+
+
+// purpose: cross-validate any classifier
+// input: the array of data, the # of folds, a seed, what to group by, and a function that trains on the first array and
+// returns a score on the second one
+// output: a vector with the score of each fold
+pub fn cross_validate<F>(arr_data: &Array2<crate::ColumnVal>, k: usize, seed: u64, group_by: GroupBy, mut train_and_score: F) -> Vec<f64>
+where
+    F: FnMut(&Array2<crate::ColumnVal>, &Array2<crate::ColumnVal>) -> f64,
+{
+    stratified_k_fold(arr_data, k, seed, group_by).iter()
+        .map(|(train, test)| train_and_score(train, test))
+        .collect()
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Module summary: shared text helpers, so that every model tokenizes comment content the same way
+// map_users_to_words() does, and weighs tokens by how many users share them.
+
+
+
+// purpose: split a comment into lowercase alphanumeric tokens
+// input: the comment content
+// output: a vector of tokens in the order they appear (repeats are kept, empty tokens are dropped)
+// splits on spaces, keeps only alphanumeric characters of each word and lowercases what is left
+pub fn tokenize(content: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    for word in content.split(' ') {
+        let token = word.chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect::<String>()
+            .to_lowercase();
+        if !token.is_empty() {
+            tokens.push(token);
+        }
+    }
+    tokens
+}
+
+
+// purpose: count how many users used each token
+// input: a map from users to the set of tokens they used (from map_users_to_words)
+// output: a hashmap from token to its # of users. The empty token map_users_to_words() can produce is left out
+pub fn token_user_counts(map: &HashMap<String, HashSet<String>>) -> HashMap<String, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for tokens in map.values() {
+        for token in tokens.iter().filter(|t| !t.is_empty()) {
+            *counts.entry(token.clone()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+
+// purpose: weigh tokens by how rare they are among users
+// input: a map from users to the set of tokens they used
+// output: a hashmap from token to its inverse document frequency ln(# users / # users who used it), so a token
+// every user used weighs 0 and a token only one user used weighs the most
+pub fn user_idf(map: &HashMap<String, HashSet<String>>) -> HashMap<String, f64> {
+    let num_users = map.len() as f64;
+    token_user_counts(map).into_iter().map(|(token, count)| (token, (num_users / count as f64).ln())).collect()
+}
@@ -0,0 +1,287 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Module summary: temporal analysis of the comments using the DATE column (col 4). It builds hourly, daily or weekly
+// series of comment volume and spam rate (overall or per video), and detects bursts: short windows in which one user
+// or one cluster of users posts many similar comments, which is what coordinated spam campaigns look like.
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+
+// how wide each bucket of a time series is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeBucket {
+    Hourly,
+    Daily,
+    Weekly, // weeks start on Monday
+}
+
+impl TimeBucket {
+    // purpose: find the start of the bucket a timestamp falls in
+    // input: a timestamp (seconds since 1970-01-01 UTC)
+    // output: the timestamp of the start of its bucket
+    pub fn start_of(&self, timestamp: i64) -> i64 {
+        match self {
+            TimeBucket::Hourly => timestamp.div_euclid(3_600) * 3_600,
+            TimeBucket::Daily => timestamp.div_euclid(SECONDS_PER_DAY) * SECONDS_PER_DAY,
+            TimeBucket::Weekly => {
+                let monday = 4 * SECONDS_PER_DAY; // 1970-01-05 was the first Monday after the epoch
+                (timestamp - monday).div_euclid(7 * SECONDS_PER_DAY) * 7 * SECONDS_PER_DAY + monday
+            },
+        }
+    }
+
+    // purpose: name the bucket a timestamp falls in
+    // input: a timestamp
+    // output: "YYYY-MM-DD HH:00" for hourly buckets, "YYYY-MM-DD" (of the first day) for daily and weekly ones
+    pub fn label_of(&self, timestamp: i64) -> String {
+        let start = self.start_of(timestamp);
+        match self {
+            TimeBucket::Hourly => format!("{} {:02}:00", format_date(start), start.rem_euclid(SECONDS_PER_DAY) / 3_600),
+            TimeBucket::Daily | TimeBucket::Weekly => format_date(start),
+        }
+    }
+}
+
+
+// purpose: count the days from 1970-01-01 to a date of the (proleptic) Gregorian calendar
+// input: year, month (1-12) and day (1-31)
+// output: the # of days, negative before 1970
+// the standard days-from-civil algorithm: it shifts the year to start in March so the leap day is the last day of the year
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+
+// purpose: the inverse of days_from_civil
+// input: the # of days since 1970-01-01
+// output: (year, month, day)
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+
+// formats the date part of a timestamp as YYYY-MM-DD
+fn format_date(timestamp: i64) -> String {
+    let (year, month, day) = civil_from_days(timestamp.div_euclid(SECONDS_PER_DAY));
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+
+// formats a timestamp the way the DATE column writes it, YYYY-MM-DDTHH:MM:SS
+pub fn format_timestamp(timestamp: i64) -> String {
+    let seconds = timestamp.rem_euclid(SECONDS_PER_DAY);
+    format!("{}T{:02}:{:02}:{:02}", format_date(timestamp), seconds / 3_600, seconds % 3_600 / 60, seconds % 60)
+}
+
+
+// purpose: read a date from the DATE column
+// input: a date like "2013-11-07T06:20:48" or "2014-01-20T15:27:47.271000" (fractions of a second are dropped)
+// output: Some(seconds since 1970-01-01 UTC), or None for an empty or malformed date
+pub fn parse_timestamp(date: &str) -> Option<i64> {
+    let (day_part, time_part) = date.trim().split_once('T')?;
+    let day_fields: Vec<i64> = day_part.split('-').map(|f| f.parse().ok()).collect::<Option<Vec<i64>>>()?;
+    let time_part = time_part.split('.').next()?;
+    let time_fields: Vec<i64> = time_part.split(':').map(|f| f.parse().ok()).collect::<Option<Vec<i64>>>()?;
+    if day_fields.len() != 3 || time_fields.len() != 3 {
+        return None;
+    }
+    let (year, month, day) = (day_fields[0], day_fields[1], day_fields[2]);
+    let (hour, minute, second) = (time_fields[0], time_fields[1], time_fields[2]);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || !(0..24).contains(&hour) || !(0..60).contains(&minute) || !(0..=60).contains(&second) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * 3_600 + minute * 60 + second)
+}
+
+
+// purpose: read the timestamp of one row
+// input: the array of data (col 4 holds the date) and a row index
+// output: Some(timestamp), or None when the row has no usable date (or the array has no date column)
+pub fn row_timestamp(arr_data: &Array2<crate::ColumnVal>, row: usize) -> Option<i64> {
+    if arr_data.ncols() <= 4 {
+        return None;
+    }
+    parse_timestamp(arr_data[[row, 4]].as_text()?)
+}
+
+
+// It is one point of a time series
+#[derive(Debug, Clone, PartialEq)]
+pub struct BucketStats {
+    pub start: i64, // timestamp of the start of the bucket
+    pub label: String,
+    pub comments: u32,
+    pub spam_comments: u32,
+    pub spam_rate: f64,
+}
+
+
+// purpose: build the time series of comment volume and spam rate
+// input: the array of data, the bucket width, and Some(video name) to only count that video's comments (None for all)
+// output: a vector of BucketStats sorted by time. Buckets with no comments are left out, and so are rows without a date
+pub fn time_series(arr_data: &Array2<crate::ColumnVal>, bucket: TimeBucket, video: Option<&str>) -> Vec<BucketStats> {
+    let mut counts: HashMap<i64, (u32, u32)> = HashMap::new(); // bucket start -> (comments, spam comments)
+    for i in 0..arr_data.nrows() {
+        if video.is_some() && arr_data[[i, 2]].as_text() != video {
+            continue;
+        }
+        if let Some(timestamp) = row_timestamp(arr_data, i) {
+            let entry = counts.entry(bucket.start_of(timestamp)).or_insert((0, 0));
+            entry.0 += 1;
+            if arr_data[[i, 3]].as_bool() == Some(true) {
+                entry.1 += 1;
+            }
+        }
+    }
+
+    let mut series: Vec<BucketStats> = counts.into_iter().map(|(start, (comments, spam_comments))| BucketStats {
+        start,
+        label: bucket.label_of(start),
+        comments,
+        spam_comments,
+        spam_rate: spam_comments as f64 / comments as f64,
+    }).collect();
+    series.sort_by_key(|point| point.start);
+    series
+}
+
+
+// purpose: build one time series per video
+// input: the array of data and the bucket width
+// output: a vector of (video name, its time series), sorted by video name
+pub fn time_series_by_video(arr_data: &Array2<crate::ColumnVal>, bucket: TimeBucket) -> Vec<(String, Vec<BucketStats>)> {
+    let mut videos: Vec<String> = arr_data.column(2).iter().map(|cell| cell.as_text().unwrap_or("").to_string()).collect::<HashSet<String>>().into_iter().collect();
+    videos.sort();
+    videos.into_iter().map(|video| {
+        let series = time_series(arr_data, bucket, Some(&video));
+        (video, series)
+    }).collect()
+}
+
+
+// It is a window in which one user (or cluster) posted many similar comments
+#[derive(Debug, Clone, PartialEq)]
+pub struct Burst {
+    pub key: String, // the user's name, or "cluster <id>"
+    pub start: i64,
+    pub end: i64, // timestamp of the last comment of the burst
+    pub rows: Vec<usize>, // the comments of the burst, in time order
+    pub mean_similarity: f64, // mean pairwise Jaccard similarity of the comments' token sets
+}
+
+
+// purpose: find the mean pairwise Jaccard similarity of some comments
+// input: the token sets of the comments
+// output: the mean over all pairs, or 1.0 if there are fewer than two comments
+fn mean_pairwise_similarity(token_sets: &[HashSet<String>]) -> f64 {
+    let mut total = 0.0;
+    let mut num_pairs = 0;
+    for i in 0..token_sets.len() {
+        for j in (i + 1)..token_sets.len() {
+            let union = token_sets[i].union(&token_sets[j]).count();
+            let shared = token_sets[i].intersection(&token_sets[j]).count();
+            total += if union == 0 { 1.0 } else { shared as f64 / union as f64 };
+            num_pairs += 1;
+        }
+    }
+    if num_pairs == 0 { 1.0 } else { total / num_pairs as f64 }
+}
+
+
+// purpose: detect bursts of similar comments
+// input: the array of data, Some(a map from user to cluster id, e.g. from find_communities) to look for bursts per
+// cluster or None to look per user, the window length in seconds, the min # of comments in a window, and the min mean
+// similarity of those comments
+// output: a vector of Bursts, the ones with the most comments first
+// sorts each user's (or cluster's) dated comments by time. From each comment, it takes the shortest run of min_comments
+// comments that fits in the window; when they are similar enough, the run is extended one comment at a time while it
+// still fits and stays similar enough, so a few near-identical comments are not diluted by unrelated ones that follow.
+// A run is a burst only when it holds more comments than the key's baseline: the median # of its comments in a window
+// starting at one of them, so a user who always posts this often is not flagged. The search then continues after the
+// burst's last comment, so bursts never overlap. Clusters of a single user are skipped, since their bursts are that
+// user's own bursts
+pub fn detect_bursts(arr_data: &Array2<crate::ColumnVal>, clusters: Option<&HashMap<String, u32>>, window_secs: i64, min_comments: usize, min_similarity: f64) -> Vec<Burst> {
+    let mut cluster_sizes: HashMap<u32, usize> = HashMap::new();
+    for id in clusters.into_iter().flat_map(|cluster_of| cluster_of.values()) {
+        *cluster_sizes.entry(*id).or_insert(0) += 1;
+    }
+    let mut rows_by_key: HashMap<String, Vec<(i64, usize)>> = HashMap::new();
+    for i in 0..arr_data.nrows() {
+        let user = arr_data[[i, 0]].as_text().unwrap_or("");
+        let key = match clusters {
+            Some(cluster_of) => match cluster_of.get(user) {
+                Some(id) if cluster_sizes[id] >= 2 => format!("cluster {}", id),
+                _ => continue,
+            },
+            None => user.to_string(),
+        };
+        if let Some(timestamp) = row_timestamp(arr_data, i) {
+            rows_by_key.entry(key).or_default().push((timestamp, i));
+        }
+    }
+
+    let mut bursts: Vec<Burst> = Vec::new();
+    let min_comments = min_comments.max(1);
+    for (key, mut rows) in rows_by_key.into_iter() {
+        if rows.len() < min_comments {
+            continue;
+        }
+        rows.sort();
+        let token_sets: Vec<HashSet<String>> = rows.iter()
+            .map(|(_, i)| crate::text_functions::tokenize(arr_data[[*i, 1]].as_text().unwrap_or("")).into_iter().collect())
+            .collect();
+        let mut window_counts: Vec<usize> = (0..rows.len())
+            .map(|k| rows[k..].iter().take_while(|(t, _)| t - rows[k].0 <= window_secs).count())
+            .collect();
+        window_counts.sort();
+        let baseline = window_counts[(window_counts.len() - 1) / 2];
+
+        let mut start = 0;
+        while start + min_comments <= rows.len() {
+            let mut end = start + min_comments - 1;
+            let mut similarity = mean_pairwise_similarity(&token_sets[start..=end]);
+            if rows[end].0 - rows[start].0 > window_secs || similarity < min_similarity {
+                start += 1;
+                continue;
+            }
+            while end + 1 < rows.len() && rows[end + 1].0 - rows[start].0 <= window_secs {
+                let extended = mean_pairwise_similarity(&token_sets[start..=end + 1]);
+                if extended < min_similarity {
+                    break;
+                }
+                end += 1;
+                similarity = extended;
+            }
+            if end + 1 - start > baseline {
+                bursts.push(Burst {
+                    key: key.clone(),
+                    start: rows[start].0,
+                    end: rows[end].0,
+                    rows: rows[start..=end].iter().map(|(_, i)| *i).collect(),
+                    mean_similarity: similarity,
+                });
+                start = end + 1;
+            } else {
+                start += 1;
+            }
+        }
+    }
+    bursts.sort_by(|a, b| b.rows.len().cmp(&a.rows.len()).then_with(|| a.start.cmp(&b.start)).then_with(|| a.key.cmp(&b.key)));
+    bursts
+}
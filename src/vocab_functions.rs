@@ -0,0 +1,132 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Module summary: ranks the vocabulary by how strongly each token points to spam or to ham. Three scores are offered:
+// the log-odds ratio with an informative Dirichlet prior (Monroe et al., 2008), the chi-square statistic, and
+// pointwise mutual information (PMI). Unlike the union of the best spammers' words, these push filler words that
+// both classes use to the bottom.
+
+
+// which score to rank the tokens by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VocabScore {
+    LogOdds, // z-score of the log-odds ratio, with the whole corpus as the prior
+    ChiSquare, // chi-square of the 2x2 table (class x comment contains the token)
+    Pmi, // PMI between the token and the class, over comments
+}
+
+
+// It holds the counts and scores of one token
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenScore {
+    pub token: String,
+    pub spam_count: u32, // # of times the token appears in spam comments
+    pub ham_count: u32,
+    pub spam_docs: u32, // # of spam comments containing it
+    pub ham_docs: u32,
+    pub log_odds: f64, // > 0 leans spam, < 0 leans ham
+    pub chi_square: f64, // how far from independent of the class, in either direction
+    pub pmi_spam: f64, // ln(P(token, spam) / (P(token) P(spam))), -inf if no spam comment has it
+    pub pmi_ham: f64,
+}
+
+impl TokenScore {
+    // whether the token is in a bigger share of spam comments than of ham comments
+    fn leans_spam(&self, total_spam_docs: u32, total_ham_docs: u32) -> bool {
+        self.spam_docs as f64 * total_ham_docs as f64 > self.ham_docs as f64 * total_spam_docs as f64
+    }
+}
+
+
+// It is the scored vocabulary plus the class sizes the scores came from
+#[derive(Debug, Clone, PartialEq)]
+pub struct VocabAnalysis {
+    pub scores: Vec<TokenScore>, // sorted by token
+    pub spam_docs: u32, // # of spam comments
+    pub ham_docs: u32,
+}
+
+
+// purpose: score every token of the comments
+// input: the array of data (cols 1-content and 3-class), and the min # of comments a token must appear in to be kept
+// (rare tokens get extreme PMI and chi-square scores from a single comment)
+// output: a VocabAnalysis
+// the log-odds prior of a token is its count in both classes together, so the prior's total is the corpus size:
+// delta = ln((y_s + a) / (n_s + a0 - y_s - a)) - ln((y_h + a) / (n_h + a0 - y_h - a)), var = 1 / (y_s + a) + 1 / (y_h + a),
+// and log_odds = delta / sqrt(var)
+pub fn score_vocabulary(arr_data: &Array2<crate::ColumnVal>, min_docs: u32) -> VocabAnalysis {
+    let mut counts: HashMap<String, [u32; 4]> = HashMap::new(); // token -> [spam count, ham count, spam docs, ham docs]
+    let (mut spam_docs, mut ham_docs) = (0, 0);
+    let (mut spam_tokens, mut ham_tokens) = (0.0, 0.0);
+    for i in 0..arr_data.nrows() {
+        let is_spam = arr_data[[i, 3]].as_bool() == Some(true);
+        let tokens = crate::text_functions::tokenize(arr_data[[i, 1]].as_text().unwrap_or(""));
+        let class = if is_spam {
+            spam_docs += 1;
+            spam_tokens += tokens.len() as f64;
+            0
+        } else {
+            ham_docs += 1;
+            ham_tokens += tokens.len() as f64;
+            1
+        };
+        for token in tokens.iter() {
+            counts.entry(token.clone()).or_insert([0; 4])[class] += 1;
+        }
+        for token in tokens.into_iter().collect::<HashSet<String>>() {
+            counts.entry(token).or_insert([0; 4])[class + 2] += 1;
+        }
+    }
+
+    let total_tokens = spam_tokens + ham_tokens;
+    let total_docs = (spam_docs + ham_docs) as f64;
+    let mut scores: Vec<TokenScore> = counts.into_iter().filter(|(_, c)| c[2] + c[3] >= min_docs.max(1)).map(|(token, c)| {
+        let (y_s, y_h) = (c[0] as f64, c[1] as f64);
+        let prior = y_s + y_h;
+        let delta = ((y_s + prior) / (spam_tokens + total_tokens - y_s - prior)).ln() - ((y_h + prior) / (ham_tokens + total_tokens - y_h - prior)).ln();
+        let variance = 1.0 / (y_s + prior) + 1.0 / (y_h + prior);
+
+        // the 2x2 table over comments: a = spam with the token, b = ham with it, c = spam without, d = ham without
+        let (a, b) = (c[2] as f64, c[3] as f64);
+        let (c_, d) = (spam_docs as f64 - a, ham_docs as f64 - b);
+        let denominator = (a + b) * (c_ + d) * (a + c_) * (b + d);
+        TokenScore {
+            token,
+            spam_count: c[0],
+            ham_count: c[1],
+            spam_docs: c[2],
+            ham_docs: c[3],
+            log_odds: delta / variance.sqrt(),
+            chi_square: if denominator > 0.0 { total_docs * (a * d - b * c_).powi(2) / denominator } else { 0.0 },
+            pmi_spam: (a * total_docs / ((a + b) * spam_docs as f64)).ln(),
+            pmi_ham: (b * total_docs / ((a + b) * ham_docs as f64)).ln(),
+        }
+    }).collect();
+    scores.sort_by(|x, y| x.token.cmp(&y.token));
+    VocabAnalysis { scores, spam_docs, ham_docs }
+}
+
+
+impl VocabAnalysis {
+
+    // purpose: rank the tokens most indicative of one class
+    // input: the score to rank by, true for spam-indicative tokens (false for ham), and how many to return
+    // output: up to n TokenScores, most indicative first (ties by token)
+    // chi-square has no direction, so it only ranks the tokens leaning towards the asked class
+    pub fn top_tokens(&self, method: VocabScore, spam: bool, n: usize) -> Vec<&TokenScore> {
+        let mut ranked: Vec<(&TokenScore, f64)> = self.scores.iter().filter_map(|score| {
+            let value = match (method, spam) {
+                (VocabScore::LogOdds, true) => score.log_odds,
+                (VocabScore::LogOdds, false) => -score.log_odds,
+                (VocabScore::ChiSquare, _) if score.leans_spam(self.spam_docs, self.ham_docs) != spam => return None,
+                (VocabScore::ChiSquare, _) => score.chi_square,
+                (VocabScore::Pmi, true) => score.pmi_spam,
+                (VocabScore::Pmi, false) => score.pmi_ham,
+            };
+            Some((score, value))
+        }).collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.token.cmp(&b.0.token)));
+        ranked.into_iter().take(n).map(|(score, _)| score).collect()
+    }
+}
@@ -0,0 +1,159 @@
+use ndarray::Array2;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+// Module summary: similarity graphs built over sliding time windows (e.g. 7-day windows moved 1 day at a time) instead
+// of over the whole dataset, and the events that happen to their communities from one window to the next (birth,
+// death, merge, split). This shows spam campaigns emerging and fading over time.
+
+
+// It is the similarity graph of the comments posted in one time window
+#[derive(Debug, Clone)]
+pub struct WindowGraph {
+    pub start: i64, // first second of the window
+    pub end: i64, // first second after the window
+    pub graph: HashMap<String, Vec<String>>,
+    pub communities: Vec<Vec<String>>, // connected subgraphs with at least 2 users, each sorted, biggest first
+    pub top_spammers: Vec<String>, // the spammers with the most spammer neighbors in this window, sorted
+}
+
+
+// what happened to the communities between two consecutive windows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventKind {
+    Birth, // a community shares no user with any community of the previous window
+    Death, // a community shares no user with any community of the next window
+    Merge, // a community shares users with two or more communities of the previous window
+    Split, // a community shares users with two or more communities of the next window
+    Continue, // a community matches exactly one community on each side
+}
+
+
+// It is one event. before/after are indices into the communities of windows[window - 1] and windows[window] (before
+// is empty for the Births of window 0)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommunityEvent {
+    pub kind: EventKind,
+    pub window: usize,
+    pub before: Vec<usize>,
+    pub after: Vec<usize>,
+}
+
+
+// purpose: find the spammers with the most spammer neighbors in a graph
+// input: a graph and the spammers in it
+// output: the sorted names of the best spammers, or an empty vector when no two spammers are connected
+// keeps only spammer-spammer edges and hands that graph to find_best_spammer()
+fn top_spammers(graph: &HashMap<String, Vec<String>>, spammers: &[String]) -> Vec<String> {
+    let spammer_set: HashSet<&String> = spammers.iter().collect();
+    let mut spam_graph: HashMap<String, Vec<String>> = HashMap::new();
+    for spammer in spammers.iter() {
+        let neighbors: Vec<String> = graph.get(spammer).map(|v| v.iter().filter(|n| spammer_set.contains(n)).cloned().collect()).unwrap_or_default();
+        spam_graph.insert(spammer.clone(), neighbors);
+    }
+    if spam_graph.values().all(|neighbors| neighbors.is_empty()) {
+        return Vec::new();
+    }
+    let mut best: Vec<String> = crate::spam_functions::find_best_spammer(&spam_graph).into_iter().collect();
+    best.sort();
+    best
+}
+
+
+// purpose: build a similarity graph for every time window
+// input: the array of data (col 4 holds the date), the window length and the step in days, and the similarity threshold
+// output: a vector of WindowGraphs in time order. Windows start at midnight of the first dated comment's day and stop
+// once they start after the last one; windows with no comments are kept (with empty graphs) so the time steps stay even
+// for each window, takes the dated rows inside it and runs the usual map_users_to_words(), create_graph() and find_spam()
+// on just those rows. Rows without a date are not in any window. Given the users' languages (from user_languages() over
+// the whole data), users whose languages differ are not linked, as with same_language_graph() on the full graph
+pub fn build_window_graphs(arr_data: &Array2<crate::ColumnVal>, window_days: i64, step_days: i64, threshold: f64,
+                           languages: Option<&HashMap<String, String>>) -> Vec<WindowGraph> {
+    let dated: Vec<(i64, usize)> = (0..arr_data.nrows())
+        .filter_map(|i| crate::time_functions::row_timestamp(arr_data, i).map(|t| (t, i)))
+        .collect();
+    let (first, last) = match (dated.iter().map(|d| d.0).min(), dated.iter().map(|d| d.0).max()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Vec::new(),
+    };
+
+    let window_secs = window_days.max(1) * 86_400;
+    let step_secs = step_days.max(1) * 86_400;
+    let mut windows: Vec<WindowGraph> = Vec::new();
+    let mut start = crate::time_functions::TimeBucket::Daily.start_of(first);
+    while start <= last {
+        let end = start + window_secs;
+        let rows: Vec<usize> = dated.iter().filter(|(t, _)| *t >= start && *t < end).map(|(_, i)| *i).collect();
+        let window_arr = crate::split_functions::select_rows(arr_data, &rows);
+
+        let (map, users) = crate::map_users_to_words(&window_arr);
+        let mut graph = crate::create_graph(&users, &map, threshold);
+        if let Some(languages) = languages {
+            graph = crate::language_functions::same_language_graph(&graph, languages);
+        }
+        let (_, spammers) = crate::spam_functions::find_spam(&window_arr, &users);
+
+        let mut members: HashMap<u32, Vec<String>> = HashMap::new();
+        for (user, id) in crate::find_communities(&graph) {
+            members.entry(id).or_default().push(user);
+        }
+        let mut communities: Vec<Vec<String>> = members.into_values().filter(|m| m.len() >= 2).collect();
+        for community in communities.iter_mut() {
+            community.sort();
+        }
+        communities.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+        windows.push(WindowGraph { start, end, top_spammers: top_spammers(&graph, &spammers), graph, communities });
+        start += step_secs;
+    }
+    windows
+}
+
+
+// purpose: track how communities evolve between consecutive windows
+// input: the windows from build_window_graphs()
+// output: a vector of CommunityEvents in window order
+// every community of the first window is a Birth. After that, it links a community of one window to a community of
+// the next one when they share at least one user, then reads the event off the # of links on each side
+pub fn community_events(windows: &[WindowGraph]) -> Vec<CommunityEvent> {
+    let mut events: Vec<CommunityEvent> = Vec::new();
+    if let Some(first) = windows.first() {
+        for a in 0..first.communities.len() {
+            events.push(CommunityEvent { kind: EventKind::Birth, window: 0, before: vec![], after: vec![a] });
+        }
+    }
+    for w in 1..windows.len() {
+        let before = &windows[w - 1].communities;
+        let after = &windows[w].communities;
+        let before_sets: Vec<HashSet<&String>> = before.iter().map(|c| c.iter().collect()).collect();
+
+        let mut links_after: Vec<Vec<usize>> = vec![Vec::new(); after.len()]; // after community -> linked before communities
+        let mut links_before: Vec<Vec<usize>> = vec![Vec::new(); before.len()];
+        for (a, community) in after.iter().enumerate() {
+            for (b, before_set) in before_sets.iter().enumerate() {
+                if community.iter().any(|user| before_set.contains(user)) {
+                    links_after[a].push(b);
+                    links_before[b].push(a);
+                }
+            }
+        }
+
+        for (b, linked) in links_before.iter().enumerate() {
+            if linked.is_empty() {
+                events.push(CommunityEvent { kind: EventKind::Death, window: w, before: vec![b], after: vec![] });
+            } else if linked.len() >= 2 {
+                events.push(CommunityEvent { kind: EventKind::Split, window: w, before: vec![b], after: linked.clone() });
+            }
+        }
+        for (a, linked) in links_after.iter().enumerate() {
+            if linked.is_empty() {
+                events.push(CommunityEvent { kind: EventKind::Birth, window: w, before: vec![], after: vec![a] });
+            } else if linked.len() >= 2 {
+                events.push(CommunityEvent { kind: EventKind::Merge, window: w, before: linked.clone(), after: vec![a] });
+            } else if links_before[linked[0]].len() == 1 {
+                events.push(CommunityEvent { kind: EventKind::Continue, window: w, before: linked.clone(), after: vec![a] });
+            }
+        }
+    }
+    events
+}